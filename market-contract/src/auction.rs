@@ -0,0 +1,179 @@
+use crate::*;
+
+#[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Bid {
+    pub bidder_id: AccountId,
+    pub amount: U128
+}
+
+// English auction, bids are escrowed in NEAR by the market
+#[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Auction {
+    pub reserve_price: U128,
+    pub start_at: U64, // nanoseconds
+    pub end_at: U64, // nanoseconds
+    pub min_bid_increment: U128,
+    pub extension: U64, // a bid placed within this window before end_at extends the auction by the same window
+    pub highest_bid: Option<Bid>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionConditions {
+    pub reserve_price: U128,
+    pub start_at: Option<U64>,
+    pub end_at: U64,
+    pub min_bid_increment: U128,
+    pub extension: Option<U64>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionArgs {
+    pub auction_conditions: AuctionConditions
+}
+
+impl AuctionConditions {
+    pub(crate) fn into_auction(self) -> Auction {
+        let start_at = self.start_at.map(| start_at | start_at.0).unwrap_or_else(env::block_timestamp);
+
+        assert!(self.end_at.0 > start_at, "end_at must be greater than start_at");
+        assert!(self.end_at.0 > env::block_timestamp(), "end_at must be in the future");
+        assert!(self.min_bid_increment.0 > 0, "min_bid_increment must be greater than 0");
+
+        Auction {
+            reserve_price: self.reserve_price,
+            start_at: U64(start_at),
+            end_at: self.end_at,
+            min_bid_increment: self.min_bid_increment,
+            extension: self.extension.unwrap_or(U64(0)),
+            highest_bid: None
+        }
+    }
+}
+
+impl Auction {
+    pub fn min_bid(&self) -> Balance {
+        if let Some(highest_bid) = &self.highest_bid {
+            highest_bid.amount.0 + self.min_bid_increment.0
+        } else {
+            self.reserve_price.0
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+
+    #[payable]
+    pub fn place_bid(&mut self, nft_contract_id: AccountId, token_id: TokenId) {
+        let deposit = env::attached_deposit();
+        let bidder_id = env::predecessor_account_id();
+
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let mut sale = self.sales.get(&contract_and_token_id).expect("Not found sale");
+        assert_ne!(bidder_id, sale.owner_id, "Can not bid on your own sale");
+
+        let auction = sale.auction.as_mut().expect("Sale is not an auction");
+
+        let now = env::block_timestamp();
+        assert!(now >= auction.start_at.0, "Auction has not started yet");
+        assert!(now < auction.end_at.0, "Auction has ended");
+
+        let min_bid = auction.min_bid();
+        assert!(deposit >= min_bid, "Attached deposit must be greater than or equal minimum bid: {}", min_bid);
+
+        // Hoàn tiền cho người trả giá cao nhất trước đó vào số dư, tự rút qua withdraw
+        let previous_bid = auction.highest_bid.replace(Bid { bidder_id: bidder_id.clone(), amount: U128(deposit) });
+
+        // Anti-sniping
+        if auction.end_at.0 - now < auction.extension.0 {
+            auction.end_at = U64(now + auction.extension.0);
+        }
+
+        emit_event(EventLogVariant::AuctionBid(vec![ AuctionBidLog {
+            nft_contract_id,
+            token_id,
+            bidder_id,
+            amount: U128(deposit),
+            end_at: auction.end_at
+        } ]));
+
+        self.sales.insert(&contract_and_token_id, &sale);
+
+        if let Some(previous_bid) = previous_bid {
+            self.internal_credit(&previous_bid.bidder_id, &NEAR_CURRENCY.to_string(), previous_bid.amount.0);
+        }
+    }
+
+    pub fn settle_auction(&mut self, nft_contract_id: AccountId, token_id: TokenId) {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let sale = self.sales.get(&contract_and_token_id).expect("Not found sale");
+
+        let auction = sale.auction.expect("Sale is not an auction");
+        assert!(env::block_timestamp() >= auction.end_at.0, "Auction has not ended yet");
+
+        if let Some(highest_bid) = auction.highest_bid {
+            self.process_purchase(
+                nft_contract_id,
+                token_id,
+                highest_bid.amount,
+                highest_bid.bidder_id
+            );
+        } else {
+            // Không có ai trả giá, huỷ đấu giá
+            self.internal_remove_sale(nft_contract_id, token_id);
+        }
+    }
+}
+
+// Dutch auction, price of each currency decays from the sale price to floor_price between start_at and end_at
+#[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DutchAuction {
+    pub floor_price: SaleConditions,
+    pub start_at: U64, // nanoseconds
+    pub end_at: U64, // nanoseconds
+    pub step: Option<U64> // price drops once per step instead of continuously
+}
+
+impl DutchAuction {
+    pub(crate) fn assert_valid(&self, start_price: &SaleConditions) {
+        assert!(self.end_at.0 > self.start_at.0, "end_at must be greater than start_at");
+        for (currency, price) in start_price {
+            let floor_price = self.floor_price.get(currency).expect("Missing floor_price for currency");
+            assert!(floor_price.0 <= price.0, "floor_price must be less than or equal start price");
+        }
+        assert_eq!(self.floor_price.len(), start_price.len(), "floor_price must have the same currencies as sale_conditions");
+        if let Some(step) = self.step {
+            assert!(step.0 > 0, "step must be greater than 0");
+        }
+    }
+
+    pub fn price_at(&self, currency: &CurrencyId, start_price: Balance, timestamp: u64) -> Balance {
+        let floor_price = self.floor_price.get(currency).map(| price | price.0).unwrap_or(start_price);
+
+        if timestamp <= self.start_at.0 {
+            return start_price;
+        }
+        if timestamp >= self.end_at.0 {
+            return floor_price;
+        }
+
+        let mut elapsed = timestamp - self.start_at.0;
+        if let Some(step) = self.step {
+            elapsed -= elapsed % step.0;
+        }
+        let duration = u128::from(self.end_at.0 - self.start_at.0);
+        let elapsed = u128::from(elapsed);
+
+        // (start_price - floor_price) * elapsed / duration có thể tràn u128, tách phần nguyên và phần dư theo duration
+        // remainder < duration và elapsed < duration đều nhỏ hơn 2^64 nên tích không tràn
+        let drop = start_price - floor_price;
+        let price_drop = drop / duration * elapsed + drop % duration * elapsed / duration;
+
+        start_price - price_drop
+    }
+}
@@ -0,0 +1,133 @@
+use crate::*;
+use near_sdk::is_promise_success;
+
+//GAS constants to attach to calls
+const GAS_FOR_FT_TRANSFER: Gas = 15_000_000_000_000;
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = 10_000_000_000_000;
+//constant used to attach 0 NEAR to a call
+const NO_DEPOSIT: Balance = 0;
+
+#[near_bindgen]
+impl Contract {
+
+    // Rút NEAR đã nhận từ các giao dịch, mặc định rút toàn bộ
+    #[payable]
+    pub fn withdraw(&mut self, amount: Option<U128>) -> U128 {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let currency = NEAR_CURRENCY.to_string();
+
+        let amount = self.internal_withdraw_amount(&account_id, &currency, amount);
+        self.internal_debit(&account_id, &currency, amount);
+
+        Promise::new(account_id).transfer(amount);
+
+        U128(amount)
+    }
+
+    // Rút FT đã nhận từ các giao dịch, account phải đăng ký storage trên FT contract
+    #[payable]
+    pub fn ft_withdraw(&mut self, ft_contract_id: AccountId, amount: Option<U128>) -> U128 {
+        assert_one_yocto();
+        assert_ne!(ft_contract_id, NEAR_CURRENCY, "Use withdraw for NEAR");
+        let account_id = env::predecessor_account_id();
+
+        let amount = self.internal_withdraw_amount(&account_id, &ft_contract_id, amount);
+        self.internal_debit(&account_id, &ft_contract_id, amount);
+
+        ext_ft_contract::ft_transfer(
+            account_id.clone(),
+            U128(amount),
+            None,
+            &ft_contract_id,
+            1,
+            GAS_FOR_FT_TRANSFER
+        ).then(ext_self::resolve_ft_withdraw(
+            account_id,
+            ft_contract_id,
+            U128(amount),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_WITHDRAW
+        ));
+
+        U128(amount)
+    }
+
+    // ft_transfer lỗi thì cộng lại số dư đã trừ
+    #[private]
+    pub fn resolve_ft_withdraw(&mut self, account_id: AccountId, ft_contract_id: AccountId, amount: U128) {
+        if !is_promise_success() {
+            self.internal_credit(&account_id, &ft_contract_id, amount.0);
+        }
+    }
+
+    // Số dư có thể rút của 1 currency ("near" hoặc FT contract id)
+    pub fn get_balance(&self, account_id: AccountId, currency: CurrencyId) -> U128 {
+        U128(self.balances.get(&(account_id, currency)).unwrap_or(0))
+    }
+
+    // Số dư của NEAR và các FT đang được chấp nhận, FT đã bị xoá khỏi danh sách vẫn xem được qua get_balance
+    pub fn get_balances(&self, account_id: AccountId) -> Vec<(CurrencyId, U128)> {
+        std::iter::once(NEAR_CURRENCY.to_string())
+        .chain(self.ft_tokens.keys())
+        .filter_map(| currency | {
+            self.balances.get(&(account_id.clone(), currency.clone()))
+            .map(| amount | (currency, U128(amount)))
+        })
+        .collect()
+    }
+
+    fn internal_withdraw_amount(&self, account_id: &AccountId, currency: &CurrencyId, amount: Option<U128>) -> Balance {
+        let balance = self.get_balance(account_id.clone(), currency.clone()).0;
+        let amount = amount.map(| amount | amount.0).unwrap_or(balance);
+
+        assert!(amount > 0, "Nothing to withdraw");
+        assert!(amount <= balance, "Amount must be less than or equal balance: {}", balance);
+
+        amount
+    }
+
+    // Cộng tiền vào số dư của account thay vì chuyển trực tiếp
+    // Mỗi entry là 1 key phẳng (account, currency), không tạo collection riêng cho từng account
+    // Entry mới tốn STORAGE_PER_BALANCE, trả bằng storage deposit còn trống của account hoặc trừ vào số NEAR được cộng
+    // Không đủ trả storage (NEAR quá nhỏ, FT mà account không có storage deposit) thì số tiền được cộng vào phí market
+    pub(crate) fn internal_credit(&mut self, account_id: &AccountId, currency: &CurrencyId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+
+        let key = (account_id.clone(), currency.clone());
+        if let Some(balance) = self.balances.get(&key) {
+            self.balances.insert(&key, &(balance + amount));
+            return;
+        }
+
+        let amount = if self.internal_take_storage_deposit(account_id, STORAGE_PER_BALANCE) {
+            amount
+        } else if currency == NEAR_CURRENCY && amount > STORAGE_PER_BALANCE {
+            amount - STORAGE_PER_BALANCE
+        } else {
+            log!("Reject credit of {} {} to {}: not enough to cover balance storage", amount, currency, account_id);
+            self.internal_add_market_fee(currency.clone(), amount);
+            return;
+        };
+
+        self.balances.insert(&key, &amount);
+    }
+
+    pub(crate) fn internal_debit(&mut self, account_id: &AccountId, currency: &CurrencyId, amount: Balance) {
+        let key = (account_id.clone(), currency.clone());
+        let balance = self.balances.get(&key).expect("Not found balance") - amount;
+
+        if balance == 0 {
+            self.balances.remove(&key);
+
+            // Phí storage của entry được trả lại vào storage deposit, rút qua storage_withdraw
+            let storage_balance = self.storage_deposit.get(account_id).unwrap_or(0);
+            self.storage_deposit.insert(account_id, &(storage_balance + STORAGE_PER_BALANCE));
+        } else {
+            self.balances.insert(&key, &balance);
+        }
+    }
+}
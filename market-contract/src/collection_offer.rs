@@ -0,0 +1,196 @@
+use crate::*;
+
+// Offer cho bất kỳ token nào của 1 nft contract (floor offer), buyer có thể mua nhiều token cùng giá
+#[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionOffer {
+    pub offer_id: OfferId,
+    pub buyer_id: AccountId,
+    pub nft_contract_id: NFTContractId,
+    pub price: SalePrice, // price per token
+    pub quantity: u32, // number of tokens left to fill
+    pub expires_at: Option<U64> // nanoseconds
+}
+
+impl CollectionOffer {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map(| expires_at | env::block_timestamp() >= expires_at.0).unwrap_or(false)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionOfferArgs {
+    pub nft_contract_id: AccountId,
+    pub quantity: u32,
+    pub expires_at: Option<U64>
+}
+
+#[near_bindgen]
+impl Contract {
+
+    // Deposit phải bằng price * quantity
+    #[payable]
+    pub fn make_collection_offer(&mut self, nft_contract_id: AccountId, price: U128, quantity: u32, expires_at: Option<U64>) -> OfferId {
+        let deposit = env::attached_deposit();
+        assert!(price.0 > 0, "Price must be greater than 0");
+        assert_eq!(deposit, price.0 * u128::from(quantity), "Attached deposit must be equal price * quantity");
+
+        self.internal_add_collection_offer(env::predecessor_account_id(), nft_contract_id, SalePrice {
+            is_native: true,
+            contract_id: NEAR_CURRENCY.to_string(),
+            amount: price
+        }, quantity, expires_at)
+    }
+
+    // Huỷ offer và hoàn lại tiền của số token chưa mua được
+    #[payable]
+    pub fn cancel_collection_offer(&mut self, offer_id: OfferId) {
+        assert_one_yocto();
+
+        let offer = self.internal_remove_collection_offer(offer_id);
+        assert!(
+            env::predecessor_account_id() == offer.buyer_id || offer.is_expired(),
+            "Only buyer can cancel an active offer"
+        );
+
+        let amount = U128(offer.price.amount.0 * u128::from(offer.quantity));
+        self.internal_refund(offer.buyer_id, &offer.price, amount);
+    }
+
+    pub fn get_collection_offer(&self, offer_id: OfferId) -> Option<CollectionOffer> {
+        self.collection_offers.get(&offer_id)
+    }
+
+    pub fn get_collection_offers_by_contract_id(&self, contract_id: NFTContractId, from_index: Option<U128>, limit: Option<u64>) -> Vec<CollectionOffer> {
+        let offer_ids = if let Some(offer_ids) = self.collection_offers_by_contract_id.get(&contract_id) {
+            offer_ids
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        offer_ids.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .map(| offer_id | self.collection_offers.get(&offer_id).unwrap())
+        .collect()
+    }
+
+    pub fn get_collection_offers_by_buyer(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<CollectionOffer> {
+        let offer_ids = if let Some(offer_ids) = self.collection_offers_by_buyer.get(&account_id) {
+            offer_ids
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        offer_ids.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .map(| offer_id | self.collection_offers.get(&offer_id).unwrap())
+        .collect()
+    }
+
+    pub(crate) fn internal_add_collection_offer(&mut self, buyer_id: AccountId, nft_contract_id: AccountId, price: SalePrice, quantity: u32, expires_at: Option<U64>) -> OfferId {
+        assert!(quantity > 0, "Quantity must be greater than 0");
+        if let Some(expires_at) = expires_at {
+            assert!(expires_at.0 > env::block_timestamp(), "expires_at must be in the future");
+        }
+
+        // Check cover storage
+        self.internal_assert_storage_available(&buyer_id, 1);
+
+        let offer_id = self.next_offer_id;
+        self.next_offer_id += 1;
+
+        let mut by_contract_id = self.collection_offers_by_contract_id.get(&nft_contract_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::InnerCollectionOffersByContractIdKey {
+                    account_id_hash: hash_account_id(&nft_contract_id)
+                }.try_to_vec().unwrap()
+            )
+        });
+        by_contract_id.insert(&offer_id);
+        self.collection_offers_by_contract_id.insert(&nft_contract_id, &by_contract_id);
+
+        let mut by_buyer = self.collection_offers_by_buyer.get(&buyer_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::InnerCollectionOffersByBuyerKey {
+                    account_id_hash: hash_account_id(&buyer_id)
+                }.try_to_vec().unwrap()
+            )
+        });
+        by_buyer.insert(&offer_id);
+        self.collection_offers_by_buyer.insert(&buyer_id, &by_buyer);
+
+        emit_event(EventLogVariant::OfferCreated(vec![ OfferLog {
+            offer_id,
+            buyer_id: buyer_id.clone(),
+            nft_contract_id: nft_contract_id.clone(),
+            token_id: None,
+            currency: price.contract_id.clone(),
+            price: price.amount,
+            quantity: Some(quantity),
+            expires_at
+        } ]));
+
+        self.collection_offers.insert(&offer_id, &CollectionOffer {
+            offer_id,
+            buyer_id,
+            nft_contract_id,
+            price,
+            quantity,
+            expires_at
+        });
+
+        offer_id
+    }
+
+    pub(crate) fn internal_remove_collection_offer(&mut self, offer_id: OfferId) -> CollectionOffer {
+        let offer = self.collection_offers.remove(&offer_id).expect("Not found collection offer");
+
+        let mut by_contract_id = self.collection_offers_by_contract_id.get(&offer.nft_contract_id).expect("Not found collection offer by contract_id");
+        by_contract_id.remove(&offer_id);
+        if by_contract_id.is_empty() {
+            self.collection_offers_by_contract_id.remove(&offer.nft_contract_id);
+        } else {
+            self.collection_offers_by_contract_id.insert(&offer.nft_contract_id, &by_contract_id);
+        }
+
+        let mut by_buyer = self.collection_offers_by_buyer.get(&offer.buyer_id).expect("Not found collection offer by buyer");
+        by_buyer.remove(&offer_id);
+        if by_buyer.is_empty() {
+            self.collection_offers_by_buyer.remove(&offer.buyer_id);
+        } else {
+            self.collection_offers_by_buyer.insert(&offer.buyer_id, &by_buyer);
+        }
+
+        emit_event(EventLogVariant::OfferRemoved(vec![ OfferRemovedLog {
+            offer_id,
+            buyer_id: offer.buyer_id.clone()
+        } ]));
+
+        offer
+    }
+
+    pub(crate) fn internal_accept_collection_offer(&mut self, nft_contract_id: AccountId, token_id: TokenId, owner_id: AccountId, approval_id: u64, offer_id: OfferId) {
+        let mut offer = self.collection_offers.get(&offer_id).expect("Not found collection offer");
+
+        assert_eq!(offer.nft_contract_id, nft_contract_id, "Offer is not for this contract");
+        assert!(!offer.is_expired(), "Offer has expired");
+        assert_ne!(offer.buyer_id, owner_id, "Can not accept your own offer");
+
+        // Hết số lượng thì xoá offer
+        offer.quantity -= 1;
+        if offer.quantity == 0 {
+            self.internal_remove_collection_offer(offer_id);
+        } else {
+            self.collection_offers.insert(&offer_id, &offer);
+        }
+
+        self.internal_settle_offer(nft_contract_id, token_id, approval_id, owner_id, offer.buyer_id, offer.price);
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt;
+use std::collections::HashMap;
+
+use near_sdk::env;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json;
+
+use crate::{CurrencyId, OfferId, SaleConditions};
+
+pub const MARKET_STANDARD_NAME: &str = "nft_market";
+pub const MARKET_METADATA_SPEC: &str = "1.0.0";
+
+/// Enum that represents the data type of the EventLog.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[serde(crate = "near_sdk::serde")]
+#[non_exhaustive]
+pub enum EventLogVariant {
+    SaleCreated(Vec<SaleLog>),
+    SaleUpdated(Vec<SaleLog>),
+    SaleRemoved(Vec<SaleRemovedLog>),
+    SalePurchased(Vec<SalePurchasedLog>),
+    OfferCreated(Vec<OfferLog>),
+    OfferRemoved(Vec<OfferRemovedLog>),
+    AuctionBid(Vec<AuctionBidLog>),
+}
+
+/// Interface to capture data about an event
+///
+/// Arguments:
+/// * `standard`: name of standard e.g. nft_market
+/// * `version`: e.g. 1.0.0
+/// * `event`: associate event data
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventLog {
+    pub standard: String,
+    pub version: String,
+
+    // `flatten` to not have "event": {<EventLogVariant>} in the JSON, just have the contents of {<EventLogVariant>}.
+    #[serde(flatten)]
+    pub event: EventLogVariant,
+}
+
+impl fmt::Display for EventLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "EVENT_JSON:{}",
+            &serde_json::to_string(self).map_err(|_| fmt::Error)?
+        ))
+    }
+}
+
+/// Log an event of the market standard
+pub(crate) fn emit_event(event: EventLogVariant) {
+    let log = EventLog {
+        standard: MARKET_STANDARD_NAME.to_string(),
+        version: MARKET_METADATA_SPEC.to_string(),
+        event,
+    };
+    env::log(log.to_string().as_bytes());
+}
+
+/// An event log to capture a listing being created or updated
+///
+/// Arguments
+/// * `owner_id`: "seller.near"
+/// * `nft_contract_id`: "nft.near"
+/// * `token_id`: "1"
+/// * `sale_conditions`: {"near": "1000", "usdc.near": "50"}
+/// * `is_auction`: true for english auctions
+/// * `expires_at`: optional expiry in nanoseconds
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleLog {
+    pub owner_id: String,
+    pub nft_contract_id: String,
+    pub token_id: String,
+    pub sale_conditions: SaleConditions,
+    pub is_auction: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<U64>,
+}
+
+/// An event log to capture a listing being removed (delisted, expired, invalid or sold)
+///
+/// Arguments
+/// * `owner_id`: "seller.near"
+/// * `nft_contract_id`: "nft.near"
+/// * `token_id`: "1"
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleRemovedLog {
+    pub owner_id: String,
+    pub nft_contract_id: String,
+    pub token_id: String,
+}
+
+/// An event log to capture a successful purchase, from a sale, auction or offer
+///
+/// Arguments
+/// * `nft_contract_id`: "nft.near"
+/// * `token_id`: "1"
+/// * `seller_id`: "seller.near"
+/// * `buyer_id`: "buyer.near"
+/// * `currency`: "near" or FT contract id
+/// * `price`: total price paid by buyer
+/// * `market_fee`: part of price kept by market
+/// * `payout`: {"seller.near": "900", "artist.near": "50"}
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SalePurchasedLog {
+    pub nft_contract_id: String,
+    pub token_id: String,
+    pub seller_id: String,
+    pub buyer_id: String,
+    pub currency: CurrencyId,
+    pub price: U128,
+    pub market_fee: U128,
+    pub payout: HashMap<String, U128>,
+}
+
+/// An event log to capture an offer being created
+///
+/// Arguments
+/// * `offer_id`: 1
+/// * `buyer_id`: "buyer.near"
+/// * `nft_contract_id`: "nft.near"
+/// * `token_id`: token of the offer, none for collection offers
+/// * `currency`: "near" or FT contract id
+/// * `price`: price per token
+/// * `quantity`: number of tokens, only for collection offers
+/// * `expires_at`: optional expiry in nanoseconds
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OfferLog {
+    pub offer_id: OfferId,
+    pub buyer_id: String,
+    pub nft_contract_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+
+    pub currency: CurrencyId,
+    pub price: U128,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<U64>,
+}
+
+/// An event log to capture an offer being cancelled or fully accepted
+///
+/// Arguments
+/// * `offer_id`: 1
+/// * `buyer_id`: "buyer.near"
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OfferRemovedLog {
+    pub offer_id: OfferId,
+    pub buyer_id: String,
+}
+
+/// An event log to capture a bid on an auction
+///
+/// Arguments
+/// * `nft_contract_id`: "nft.near"
+/// * `token_id`: "1"
+/// * `bidder_id`: "bidder.near"
+/// * `amount`: bid in NEAR
+/// * `end_at`: auction end after anti-sniping extension
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionBidLog {
+    pub nft_contract_id: String,
+    pub token_id: String,
+    pub bidder_id: String,
+    pub amount: U128,
+    pub end_at: U64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nep_format_sale_created() {
+        let expected = r#"EVENT_JSON:{"standard":"nft_market","version":"1.0.0","event":"sale_created","data":[{"owner_id":"seller.near","nft_contract_id":"nft.near","token_id":"1","sale_conditions":{"near":"1000"},"is_auction":false}]}"#;
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert("near".to_string(), U128(1000));
+        let log = EventLog {
+            standard: "nft_market".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::SaleCreated(vec![SaleLog {
+                owner_id: "seller.near".to_string(),
+                nft_contract_id: "nft.near".to_string(),
+                token_id: "1".to_string(),
+                sale_conditions,
+                is_auction: false,
+                expires_at: None,
+            }]),
+        };
+        assert_eq!(expected, log.to_string());
+    }
+
+    #[test]
+    fn nep_format_sale_purchased() {
+        let expected = r#"EVENT_JSON:{"standard":"nft_market","version":"1.0.0","event":"sale_purchased","data":[{"nft_contract_id":"nft.near","token_id":"1","seller_id":"seller.near","buyer_id":"buyer.near","currency":"usdc.near","price":"1000","market_fee":"25","payout":{"seller.near":"975"}}]}"#;
+        let mut payout = HashMap::new();
+        payout.insert("seller.near".to_string(), U128(975));
+        let log = EventLog {
+            standard: "nft_market".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::SalePurchased(vec![SalePurchasedLog {
+                nft_contract_id: "nft.near".to_string(),
+                token_id: "1".to_string(),
+                seller_id: "seller.near".to_string(),
+                buyer_id: "buyer.near".to_string(),
+                currency: "usdc.near".to_string(),
+                price: U128(1000),
+                market_fee: U128(25),
+                payout,
+            }]),
+        };
+        assert_eq!(expected, log.to_string());
+    }
+
+    #[test]
+    fn nep_format_collection_offer_created() {
+        let expected = r#"EVENT_JSON:{"standard":"nft_market","version":"1.0.0","event":"offer_created","data":[{"offer_id":7,"buyer_id":"buyer.near","nft_contract_id":"nft.near","currency":"near","price":"500","quantity":3}]}"#;
+        let log = EventLog {
+            standard: "nft_market".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::OfferCreated(vec![OfferLog {
+                offer_id: 7,
+                buyer_id: "buyer.near".to_string(),
+                nft_contract_id: "nft.near".to_string(),
+                token_id: None,
+                currency: "near".to_string(),
+                price: U128(500),
+                quantity: Some(3),
+                expires_at: None,
+            }]),
+        };
+        assert_eq!(expected, log.to_string());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::*;
+use near_sdk::is_promise_success;
+
+//GAS constants to attach to calls
+const GAS_FOR_FT_TRANSFER: Gas = 15_000_000_000_000;
+const GAS_FOR_RESOLVE_WITHDRAW: Gas = 10_000_000_000_000;
+//constant used to attach 0 NEAR to a call
+const NO_DEPOSIT: Balance = 0;
+
+// 10_000 basis points = 100%
+pub const FEE_DENOMINATOR: u32 = 10_000;
+// Phí tối đa owner được cấu hình (10%), để phí thay đổi sau khi seller đăng bán vẫn có giới hạn
+pub const MAX_MARKET_FEE: u32 = 1_000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleMarketFee {
+    pub market_fee: u32, // basis points
+    pub price: U128,
+    pub fee_amount: U128,
+    pub net_proceeds: U128 // price - fee_amount, before royalties
+}
+
+#[near_bindgen]
+impl Contract {
+
+    // Owner cấu hình phí market (basis points)
+    #[payable]
+    pub fn set_market_fee(&mut self, market_fee: u32) {
+        assert_one_yocto();
+        self.assert_owner();
+        assert!(market_fee <= MAX_MARKET_FEE, "Market fee can not be greater than {}", MAX_MARKET_FEE);
+
+        self.market_fee = market_fee;
+    }
+
+    pub fn get_market_fee(&self) -> u32 {
+        self.market_fee
+    }
+
+    // Phí riêng cho partner collection, None để xoá
+    #[payable]
+    pub fn set_contract_market_fee(&mut self, nft_contract_id: AccountId, market_fee: Option<u32>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        if let Some(market_fee) = market_fee {
+            assert!(market_fee <= MAX_MARKET_FEE, "Market fee can not be greater than {}", MAX_MARKET_FEE);
+            self.market_fee_by_contract_id.insert(&nft_contract_id, &market_fee);
+        } else {
+            self.market_fee_by_contract_id.remove(&nft_contract_id);
+        }
+    }
+
+    // Phí riêng cho seller (vd. 0 cho account khuyến mãi), None để xoá
+    #[payable]
+    pub fn set_seller_market_fee(&mut self, seller_id: AccountId, market_fee: Option<u32>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        if let Some(market_fee) = market_fee {
+            assert!(market_fee <= MAX_MARKET_FEE, "Market fee can not be greater than {}", MAX_MARKET_FEE);
+            self.market_fee_by_seller.insert(&seller_id, &market_fee);
+        } else {
+            self.market_fee_by_seller.remove(&seller_id);
+        }
+    }
+
+    pub fn get_contract_market_fees(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<(AccountId, u32)> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.market_fee_by_contract_id.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .collect()
+    }
+
+    pub fn get_seller_market_fees(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<(AccountId, u32)> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.market_fee_by_seller.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .collect()
+    }
+
+    // Phí thực tế áp dụng cho seller khi bán token của nft_contract_id
+    pub fn get_effective_market_fee(&self, nft_contract_id: AccountId, seller_id: AccountId) -> u32 {
+        self.internal_market_fee_rate(&nft_contract_id, &seller_id)
+    }
+
+    // Phí và số tiền còn lại (trước royalties) nếu sale được mua với giá hiện tại
+    pub fn get_sale_market_fee(&self, nft_contract_id: AccountId, token_id: TokenId, currency: CurrencyId) -> SaleMarketFee {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let sale = self.sales.get(&contract_and_token_id).expect("Not found sale");
+
+        let price = sale.current_price(&currency).expect("Sale does not accept this currency").0;
+        let market_fee = self.internal_market_fee_rate(&nft_contract_id, &sale.owner_id);
+        let fee_amount = self.internal_market_fee(&nft_contract_id, &sale.owner_id, price);
+
+        SaleMarketFee {
+            market_fee,
+            price: U128(price),
+            fee_amount: U128(fee_amount),
+            net_proceeds: U128(price - fee_amount)
+        }
+    }
+
+    // Phí market đã thu của 1 currency ("near" hoặc FT contract id)
+    pub fn get_accrued_fee(&self, currency: AccountId) -> U128 {
+        U128(self.accrued_fees.get(&currency).unwrap_or(0))
+    }
+
+    pub fn get_accrued_fees(&self) -> Vec<(AccountId, U128)> {
+        self.accrued_fees.iter()
+        .map(| (currency, amount) | (currency, U128(amount)))
+        .collect()
+    }
+
+    // Owner rút phí market về treasury, mặc định rút toàn bộ về owner_id
+    #[payable]
+    pub fn withdraw_market_fee(&mut self, currency: AccountId, amount: Option<U128>, receiver_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let receiver_id = receiver_id.unwrap_or_else(|| self.owner_id.clone());
+        let accrued = self.accrued_fees.get(&currency).unwrap_or(0);
+        let amount = amount.map(| amount | amount.0).unwrap_or(accrued);
+
+        assert!(amount > 0, "Nothing to withdraw");
+        assert!(amount <= accrued, "Amount must be less than or equal accrued fee: {}", accrued);
+
+        self.internal_sub_market_fee(&currency, amount);
+
+        if currency == NEAR_CURRENCY {
+            Promise::new(receiver_id).transfer(amount);
+        } else {
+            ext_ft_contract::ft_transfer(
+                receiver_id,
+                U128(amount),
+                None,
+                &currency,
+                1,
+                GAS_FOR_FT_TRANSFER
+            ).then(ext_self::resolve_withdraw_market_fee(
+                currency,
+                U128(amount),
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_RESOLVE_WITHDRAW
+            ));
+        }
+    }
+
+    // ft_transfer lỗi thì cộng lại phí đã trừ
+    #[private]
+    pub fn resolve_withdraw_market_fee(&mut self, currency: AccountId, amount: U128) {
+        if !is_promise_success() {
+            self.internal_add_market_fee(currency, amount.0);
+        }
+    }
+
+    // Phí áp dụng theo thứ tự ưu tiên: seller > nft contract > mặc định
+    pub(crate) fn internal_market_fee_rate(&self, nft_contract_id: &AccountId, seller_id: &AccountId) -> u32 {
+        self.market_fee_by_seller.get(seller_id)
+        .or_else(|| self.market_fee_by_contract_id.get(nft_contract_id))
+        .unwrap_or(self.market_fee)
+    }
+
+    pub(crate) fn internal_market_fee(&self, nft_contract_id: &AccountId, seller_id: &AccountId, price: Balance) -> Balance {
+        let market_fee = u128::from(self.internal_market_fee_rate(nft_contract_id, seller_id));
+        let denominator = u128::from(FEE_DENOMINATOR);
+
+        // Tách phần nguyên và phần dư để price * market_fee không tràn u128
+        price / denominator * market_fee + price % denominator * market_fee / denominator
+    }
+
+    pub(crate) fn internal_add_market_fee(&mut self, currency: AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+
+        let accrued = self.accrued_fees.get(&currency).unwrap_or(0);
+        self.accrued_fees.insert(&currency, &(accrued + amount));
+    }
+
+    pub(crate) fn internal_sub_market_fee(&mut self, currency: &AccountId, amount: Balance) {
+        let accrued = self.accrued_fees.get(currency).unwrap_or(0) - amount;
+
+        if accrued == 0 {
+            self.accrued_fees.remove(currency);
+        } else {
+            self.accrued_fees.insert(currency, &accrued);
+        }
+    }
+}
@@ -1,10 +1,9 @@
 use crate::*;
-use near_sdk::promise_result_as_success;
 
 //GAS constants to attach to calls
-const GAS_FOR_ROYALTIES: Gas = 115_000_000_000_000;
+// resolve callback chỉ cộng số dư cho tối đa 10 account payout
+const GAS_FOR_ROYALTIES: Gas = 30_000_000_000_000;
 const GAS_FOR_NFT_TRANSFER: Gas = 15_000_000_000_000;
-const GAS_FOR_FT_TRANSFER: Gas = 15_000_000_000_000;
 //constant used to attach 0 NEAR to a call
 const NO_DEPOSIT: Balance = 0;
 
@@ -15,6 +14,28 @@ pub struct FTSaleArgs {
     pub token_id: TokenId
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FTOfferArgs {
+    pub offer: OfferArgs
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FTCollectionOfferArgs {
+    pub collection_offer: CollectionOfferArgs
+}
+
+// msg của ft_transfer_call: mua token đang đăng bán hoặc tạo offer
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+pub enum FTOnTransferArgs {
+    Sale(FTSaleArgs),
+    Offer(FTOfferArgs),
+    CollectionOffer(FTCollectionOfferArgs)
+}
+
 pub trait FungibleTokenReceiver {
     fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128>;
 }
@@ -28,20 +49,54 @@ pub trait FungibleTokenCore {
 impl FungibleTokenReceiver for Contract {
     fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
         let ft_contract_id = env::predecessor_account_id();
-
-        let FTSaleArgs { nft_contract_id, token_id } = near_sdk::serde_json::from_str(&msg).expect("Not valid FT Sale args");
+        // "near" cũng là account id hợp lệ, không được coi là FT contract
+        assert_ne!(ft_contract_id, NEAR_CURRENCY, "Native currency can not be paid with ft_on_transfer");
+        self.internal_assert_currency_allowed(&ft_contract_id);
+
+        let args: FTOnTransferArgs = near_sdk::serde_json::from_str(&msg).expect("Not valid FT Sale args");
+
+        let FTSaleArgs { nft_contract_id, token_id } = match args {
+            FTOnTransferArgs::Sale(sale_args) => sale_args,
+            FTOnTransferArgs::Offer(FTOfferArgs { offer }) => {
+                self.internal_add_offer(sender_id, offer.nft_contract_id, offer.token_id, SalePrice {
+                    is_native: false,
+                    contract_id: ft_contract_id,
+                    amount
+                }, offer.expires_at);
+
+                return PromiseOrValue::Value(U128(0));
+            },
+            FTOnTransferArgs::CollectionOffer(FTCollectionOfferArgs { collection_offer }) => {
+                let quantity = u128::from(collection_offer.quantity);
+                assert!(quantity > 0, "Quantity must be greater than 0");
+                assert_eq!(amount.0 % quantity, 0, "Amount must be divisible by quantity");
+
+                self.internal_add_collection_offer(sender_id, collection_offer.nft_contract_id, SalePrice {
+                    is_native: false,
+                    contract_id: ft_contract_id,
+                    amount: U128(amount.0 / quantity)
+                }, collection_offer.quantity, collection_offer.expires_at);
+
+                return PromiseOrValue::Value(U128(0));
+            }
+        };
         let contract_and_token_id: ContractAndTokenId = format!("{}{}{}", nft_contract_id, ".", token_id);
         let sale = self.sales.get(&contract_and_token_id).expect("Not found sale");
 
-        let price = sale.sale_conditions;
-        assert!(amount.0 >= price.amount.0, "Amount should be greater than NFT price");
+        assert!(sale.auction.is_none(), "Auction sales only accept bids in NEAR");
+        assert!(!sale.is_expired(), "Sale has expired");
+
+        // Chỉ thu đúng giá hiện tại, phần thừa được trả lại qua giá trị trả về của ft_on_transfer
+        let price = sale.current_price(&ft_contract_id).expect("Sale does not accept this token");
+        assert!(amount.0 >= price.0, "Amount should be greater than NFT price");
         assert_ne!(sender_id, sale.owner_id, "Can not bid on your own sale");
-        assert_eq!(ft_contract_id, price.contract_id, "Payout same token in sale");
 
         self.ft_process_purchase(
             nft_contract_id, 
             token_id, 
+            ft_contract_id,
             price, 
+            amount,
             sender_id
         )
     }
@@ -49,76 +104,76 @@ impl FungibleTokenReceiver for Contract {
 
 #[near_bindgen]
 impl Contract {
-    pub(crate) fn ft_process_purchase(&mut self, nft_contract_id: AccountId, token_id: TokenId, price: SalePrice, buyer_id: AccountId) -> PromiseOrValue<U128> {
+    pub(crate) fn ft_process_purchase(&mut self, nft_contract_id: AccountId, token_id: TokenId, ft_contract_id: AccountId, price: U128, amount: U128, buyer_id: AccountId) -> PromiseOrValue<U128> {
         let sale = self.internal_remove_sale(nft_contract_id.clone(), token_id.clone());
 
+        // Phí market được trừ trước khi chia cho seller và royalties
+        let market_fee = self.internal_market_fee(&nft_contract_id, &sale.owner_id, price.0);
+
         ext_nft_contract::nft_transfer_payout(
             buyer_id.clone(), 
-            token_id, 
+            token_id.clone(), 
             sale.approval_id, 
             "Payout from market contract".to_string(), 
-            price.amount, 
+            U128(price.0 - market_fee), 
             10, 
             &nft_contract_id, 
             1, 
             GAS_FOR_NFT_TRANSFER
         ).then(ext_self::ft_resolve_purchase(
-            buyer_id, 
-            price,
+            Purchase {
+                nft_contract_id,
+                token_id,
+                seller_id: sale.owner_id,
+                buyer_id,
+                currency: ft_contract_id,
+                price,
+                market_fee: U128(market_fee)
+            },
+            amount,
             &env::current_account_id(), 
             NO_DEPOSIT, 
             GAS_FOR_ROYALTIES
         )).into()
     }
 
-    pub fn ft_resolve_purchase(&mut self, buyer_id: AccountId, price: SalePrice) -> U128 {
-        let payout_option = promise_result_as_success().and_then(| value | {
-            let payout_object: Payout = near_sdk::serde_json::from_slice::<Payout>(&value).expect("Invalid payout object");
-
-            if payout_object.payout.len() > 10 || payout_object.payout.is_empty() {
-                env::log("Cannot have more than 10 royalities".as_bytes());
-                None
-            } else {
-                let mut remainder = price.amount.0;
-
-                for &value in payout_object.payout.values() {
-                    remainder = remainder.checked_sub(value.0)?;
-                }
-
-                if remainder == 0 || remainder == 1 {
-                    Some(payout_object.payout)
-                } else {
-                    None
-                }
-            }
-        });
+    // Trả về số FT không dùng đến để FT contract hoàn lại cho buyer (NEP-141)
+    #[private]
+    pub fn ft_resolve_purchase(&mut self, purchase: Purchase, amount: U128) -> U128 {
+        let price = purchase.price;
+
+        if self.internal_ft_payout(purchase) {
+            U128(amount.0 - price.0)
+        } else {
+            amount
+        }
+    }
 
-        let payout = if let Some(payout_option) = payout_option {
-            payout_option
+    // Offer đã được giữ trong market nên khi lỗi cộng lại vào số dư của buyer
+    #[private]
+    pub fn ft_resolve_offer_purchase(&mut self, purchase: Purchase) {
+        let (buyer_id, currency, price) = (purchase.buyer_id.clone(), purchase.currency.clone(), purchase.price);
+
+        if !self.internal_ft_payout(purchase) {
+            self.internal_credit(&buyer_id, &currency, price.0);
+        }
+    }
+
+    // Chia tiền cho seller + royalties theo payout, trả về false nếu nft_transfer_payout lỗi
+    pub(crate) fn internal_ft_payout(&mut self, purchase: Purchase) -> bool {
+        let payout = if let Some(payout) = purchase.valid_payout() {
+            payout
         } else {
-              ext_ft_contract::ft_transfer(
-                buyer_id.clone(),
-                price.amount,
-                None,
-                &price.contract_id,
-                1,
-                GAS_FOR_FT_TRANSFER
-            );
-            
-            return U128(0);
+            return false;
         };
 
-        for (reciver_id, amount) in payout {
-            ext_ft_contract::ft_transfer(
-                reciver_id.clone(),
-                amount,
-                None,
-                &price.contract_id,
-                1,
-                GAS_FOR_FT_TRANSFER
-            );
+        // Cộng vào số dư, các account tự rút qua ft_withdraw
+        for (reciver_id, amount) in payout.iter() {
+            self.internal_credit(reciver_id, &purchase.currency, amount.0);
         }
+        self.internal_add_market_fee(purchase.currency.clone(), purchase.market_fee.0);
+        purchase.emit_purchased(payout);
 
-        U128(0)
+        true
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,55 @@
+use crate::*;
+
+// Thông tin FT được market chấp nhận thanh toán
+#[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FTToken {
+    pub decimals: u8,
+    pub symbol: String
+}
+
+#[near_bindgen]
+impl Contract {
+
+    // Owner thêm/cập nhật FT được chấp nhận
+    #[payable]
+    pub fn add_ft_token(&mut self, ft_contract_id: AccountId, decimals: u8, symbol: String) {
+        assert_one_yocto();
+        self.assert_owner();
+        assert_ne!(ft_contract_id, NEAR_CURRENCY, "Can not add native currency as FT");
+
+        self.ft_tokens.insert(&ft_contract_id, &FTToken {
+            decimals,
+            symbol
+        });
+    }
+
+    // Sale đang dùng FT bị xoá vẫn giữ nguyên, nhưng không thể mua bằng FT đó
+    #[payable]
+    pub fn remove_ft_token(&mut self, ft_contract_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.ft_tokens.remove(&ft_contract_id).expect("Not found ft token");
+    }
+
+    pub fn get_ft_token(&self, ft_contract_id: AccountId) -> Option<FTToken> {
+        self.ft_tokens.get(&ft_contract_id)
+    }
+
+    pub fn get_ft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<(AccountId, FTToken)> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.ft_tokens.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .collect()
+    }
+
+    pub(crate) fn internal_assert_currency_allowed(&self, currency: &CurrencyId) {
+        assert!(
+            currency == NEAR_CURRENCY || self.ft_tokens.get(currency).is_some(),
+            "Currency {} is not accepted by market", currency
+        );
+    }
+}
@@ -2,6 +2,101 @@ use crate::*;
 
 #[near_bindgen]
 impl Contract {
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only contract owner can call this method");
+    }
+
+    // Số lượng sale, voucher và offer mà account đang phải cover storage
+    pub(crate) fn internal_storage_items(&self, account_id: &AccountId) -> u128 {
+        let sales = self.by_owner_id.get(account_id).map(| s | s.len()).unwrap_or_default();
+        let vouchers = self.vouchers_by_creator.get(account_id).map(| s | s.len()).unwrap_or_default();
+        let offers = self.offers_by_buyer.get(account_id).map(| s | s.len()).unwrap_or_default();
+        let collection_offers = self.collection_offers_by_buyer.get(account_id).map(| s | s.len()).unwrap_or_default();
+
+        u128::from(sales + vouchers + offers + collection_offers)
+    }
+
+    pub(crate) fn internal_assert_storage_available(&self, account_id: &AccountId, new_items: u128) {
+        let storage_balance = self.storage_deposit.get(account_id).unwrap_or(0);
+        let storage_required = (self.internal_storage_items(account_id) + new_items) * STORAGE_PER_SALE;
+
+        assert!(storage_balance >= storage_required, "Storage balance not enough for cover storage staking");
+    }
+
+    // Trừ amount từ phần storage deposit không còn sử dụng, trả về false nếu không đủ
+    pub(crate) fn internal_take_storage_deposit(&mut self, account_id: &AccountId, amount: Balance) -> bool {
+        let storage_balance = self.storage_deposit.get(account_id).unwrap_or(0);
+        let storage_required = self.internal_storage_items(account_id) * STORAGE_PER_SALE;
+
+        if storage_balance < storage_required + amount {
+            return false;
+        }
+
+        self.storage_deposit.insert(account_id, &(storage_balance - amount));
+        true
+    }
+
+    // Trừ bounty từ phần storage deposit không còn sử dụng của seller
+    pub(crate) fn internal_take_expired_sale_bounty(&mut self, owner_id: &AccountId) -> Balance {
+        let storage_balance = self.storage_deposit.get(owner_id).unwrap_or(0);
+        let storage_required = self.internal_storage_items(owner_id) * STORAGE_PER_SALE;
+        let bounty = storage_balance.saturating_sub(storage_required).min(EXPIRED_SALE_BOUNTY);
+
+        if bounty > 0 {
+            self.storage_deposit.insert(owner_id, &(storage_balance - bounty));
+        }
+
+        bounty
+    }
+
+    // Xoá sale không còn hợp lệ, hoàn tiền vào số dư của người đang trả giá cao nhất nếu là đấu giá
+    pub(crate) fn internal_delist(&mut self, nft_contract_id: AccountId, token_id: TokenId) -> Sale {
+        let sale = self.internal_remove_sale(nft_contract_id, token_id);
+
+        if let Some(highest_bid) = sale.auction.as_ref().and_then(| auction | auction.highest_bid.clone()) {
+            self.internal_credit(&highest_bid.bidder_id, &NEAR_CURRENCY.to_string(), highest_bid.amount.0);
+        }
+
+        sale
+    }
+
+    pub(crate) fn internal_add_sale(&mut self, sale: Sale) {
+        let contract_and_token_id = format!("{}{}{}", sale.nft_contract_id, ".", sale.token_id);
+
+        if let Some(old_sale) = self.sales.get(&contract_and_token_id) {
+            let has_bid = old_sale.auction.map(| auction | auction.highest_bid.is_some()).unwrap_or(false);
+            assert!(!has_bid, "Can not relist an auction with active bids");
+
+            self.internal_remove_sale(old_sale.nft_contract_id, old_sale.token_id);
+        }
+
+        self.sales.insert(&contract_and_token_id, &sale);
+
+        let mut by_owner_id = self.by_owner_id.get(&sale.owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::InnterByOwnerIdKey {
+                    account_id_hash: hash_account_id(&sale.owner_id)
+                }.try_to_vec().unwrap()
+            )
+        });
+
+        by_owner_id.insert(&contract_and_token_id);
+        self.by_owner_id.insert(&sale.owner_id, &by_owner_id);
+
+        let mut by_contract_id = self.by_contract_id.get(&sale.nft_contract_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::InnerByContractIdKey {
+                    account_id_hash: hash_account_id(&sale.nft_contract_id)
+                }.try_to_vec().unwrap()
+            )
+        });
+
+        by_contract_id.insert(&sale.token_id);
+        self.by_contract_id.insert(&sale.nft_contract_id, &by_contract_id);
+
+        emit_event(EventLogVariant::SaleCreated(vec![ sale.to_log() ]));
+    }
+
     pub(crate) fn internal_remove_sale(&mut self, nft_contract_id: AccountId, token_id: TokenId) -> Sale {
         let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
 
@@ -24,6 +119,12 @@ impl Contract {
             self.by_contract_id.insert(&nft_contract_id, &by_contract_id);
         }
 
+        emit_event(EventLogVariant::SaleRemoved(vec![ SaleRemovedLog {
+            owner_id: sale.owner_id.clone(),
+            nft_contract_id,
+            token_id
+        } ]));
+
         sale
     }
 }
\ No newline at end of file
@@ -1,16 +1,30 @@
-use near_sdk::{AccountId, near_bindgen, PanicOnDefault, Balance, env, Promise, CryptoHash, ext_contract, Gas, PromiseOrValue};
+use near_sdk::{AccountId, near_bindgen, PanicOnDefault, Balance, env, Promise, CryptoHash, ext_contract, Gas, PromiseOrValue, log};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Serialize, Deserialize};
 use near_sdk::collections::{LookupMap, UnorderedSet, UnorderedMap};
+use std::collections::HashMap;
 
 pub use crate::sale_view::*;
 pub use crate::utils::*;
 pub use crate::nft_callback::*;
 pub use crate::sale::*;
 pub use crate::ft_callback::*;
+pub use crate::auction::*;
+pub use crate::offer::*;
+pub use crate::collection_offer::*;
+pub use crate::fee::*;
+pub use crate::ft_token::*;
+pub use crate::event::*;
+pub use crate::voucher::*;
 
 const STORAGE_PER_SALE: u128 = 1000 * env::STORAGE_PRICE_PER_BYTE;
+// Storage of one (account, currency) entry in the balance ledger
+const STORAGE_PER_BALANCE: u128 = 200 * env::STORAGE_PRICE_PER_BYTE;
+// Bounty paid from the seller's storage deposit to whoever removes an expired sale
+const EXPIRED_SALE_BOUNTY: u128 = STORAGE_PER_SALE / 10;
+// Currency id used for sales paid in NEAR
+pub const NEAR_CURRENCY: &str = "near";
 
 mod sale_view;
 mod utils;
@@ -18,14 +32,34 @@ mod sale;
 mod nft_callback;
 mod internal;
 mod ft_callback;
+mod auction;
+mod offer;
+mod collection_offer;
+mod fee;
+mod ft_token;
+mod balance;
+mod event;
+mod voucher;
 
 pub type TokenId = String;
 pub type NFTContractId = String;
 pub type ContractAndTokenId = String; //nft-tutorial.vbidev.testnet.VBI_NFT#01
+pub type OfferId = u64;
+pub type CurrencyId = AccountId; // "near" hoặc FT contract id
+pub type SaleConditions = HashMap<CurrencyId, U128>; // các currency được chấp nhận và giá tương ứng
 
 #[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct SalePrice {
+    is_native: bool,
+    contract_id: AccountId,
+    amount: U128
+}
+
+// Giá của sale trước khi hỗ trợ nhiều currency
+#[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SalePriceV2 {
     is_native: bool,
     contract_id: AccountId,
     decimals: U64,
@@ -34,12 +68,12 @@ pub struct SalePrice {
 
 #[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
 #[serde(crate = "near_sdk::serde")]
-pub struct SaleV1 {
+pub struct SaleV2 {
     pub owner_id: AccountId,
     pub approval_id: u64,
     pub nft_contract_id: NFTContractId,
     pub token_id: TokenId,
-    pub sale_conditions: U128
+    pub sale_conditions: SalePriceV2
 }
 
 #[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
@@ -49,17 +83,43 @@ pub struct Sale {
     pub approval_id: u64,
     pub nft_contract_id: NFTContractId,
     pub token_id: TokenId,
-    pub sale_conditions: SalePrice
+    pub sale_conditions: SaleConditions,
+    pub auction: Option<Auction>,
+    pub dutch_auction: Option<DutchAuction>,
+    pub expires_at: Option<U64> // nanoseconds
+}
+
+impl From<SaleV2> for Sale {
+    fn from(sale: SaleV2) -> Self {
+        let currency = if sale.sale_conditions.is_native {
+            NEAR_CURRENCY.to_string()
+        } else {
+            sale.sale_conditions.contract_id
+        };
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(currency, sale.sale_conditions.amount);
+
+        Self {
+            owner_id: sale.owner_id,
+            approval_id: sale.approval_id,
+            nft_contract_id: sale.nft_contract_id,
+            token_id: sale.token_id,
+            sale_conditions,
+            auction: None,
+            dutch_auction: None,
+            expires_at: None
+        }
+    }
 }
 
 
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct ContractV1 {
+pub struct ContractV2 {
     // Owner of contract
     pub owner_id: AccountId,
 
     // Sales của token
-    pub sales: UnorderedMap<ContractAndTokenId, SaleV1>,
+    pub sales: UnorderedMap<ContractAndTokenId, SaleV2>,
 
     // Danh sách sales theo account id
     pub by_owner_id: LookupMap<AccountId, UnorderedSet<ContractAndTokenId>>,
@@ -87,19 +147,89 @@ pub struct Contract {
     pub by_contract_id: LookupMap<NFTContractId, UnorderedSet<TokenId>>,
 
     // Danh sách account deposit để cover storage
-    pub storage_deposit: LookupMap<AccountId, Balance>
+    pub storage_deposit: LookupMap<AccountId, Balance>,
+
+    // Offer id tiếp theo
+    pub next_offer_id: u64,
+
+    // Offers (bids) của buyer cho token chưa cần đăng bán
+    pub offers: UnorderedMap<OfferId, Offer>,
+
+    // Danh sách offer theo token
+    pub offers_by_token: LookupMap<ContractAndTokenId, UnorderedSet<OfferId>>,
+
+    // Danh sách offer theo buyer
+    pub offers_by_buyer: LookupMap<AccountId, UnorderedSet<OfferId>>,
+
+    // Offers cho bất kỳ token nào của 1 nft contract
+    pub collection_offers: UnorderedMap<OfferId, CollectionOffer>,
+
+    // Danh sách collection offer theo nft contract
+    pub collection_offers_by_contract_id: LookupMap<NFTContractId, UnorderedSet<OfferId>>,
+
+    // Danh sách collection offer theo buyer
+    pub collection_offers_by_buyer: LookupMap<AccountId, UnorderedSet<OfferId>>,
+
+    // Phí market (basis points)
+    pub market_fee: u32,
+
+    // Phí market đã thu theo currency ("near" hoặc FT contract id)
+    pub accrued_fees: UnorderedMap<AccountId, Balance>,
+
+    // Phí market riêng theo nft contract (partner collections)
+    pub market_fee_by_contract_id: UnorderedMap<NFTContractId, u32>,
+
+    // Phí market riêng theo seller
+    pub market_fee_by_seller: UnorderedMap<AccountId, u32>,
+
+    // Danh sách FT được chấp nhận thanh toán
+    pub ft_tokens: UnorderedMap<AccountId, FTToken>,
+
+    // Số dư có thể rút theo account và currency (seller, royalties, refund)
+    pub balances: LookupMap<(AccountId, CurrencyId), Balance>,
+
+    // Voucher (token chưa mint) đang được đăng bán
+    pub vouchers: UnorderedMap<ContractAndTokenId, VoucherListing>,
+
+    // Danh sách voucher theo creator
+    pub vouchers_by_creator: LookupMap<AccountId, UnorderedSet<ContractAndTokenId>>
 }
 
-impl From<ContractV1> for Contract {
-    fn from(contract: ContractV1) -> Self {
-        // Remove all old sale type
-        let sales = UnorderedMap::new(StorageKey::SaleKey.try_to_vec().unwrap());
+impl From<ContractV2> for Contract {
+    fn from(contract: ContractV2) -> Self {
+        // Đọc hết sale cũ rồi xoá index, key và value cũ trước khi dùng lại prefix SaleKey
+        let mut old_sales = contract.sales;
+        let sales_v2 = old_sales.to_vec();
+        old_sales.clear();
+
+        // Giữ nguyên sale nên by_owner_id, by_contract_id vẫn khớp với sales
+        // Sale bằng FT vẫn giữ currency cũ, owner cần add_ft_token để nhận thanh toán bằng FT đó
+        let mut sales = UnorderedMap::new(StorageKey::SaleKey.try_to_vec().unwrap());
+        for (contract_and_token_id, sale) in sales_v2 {
+            sales.insert(&contract_and_token_id, &Sale::from(sale));
+        }
+
         Self {
             owner_id: contract.owner_id,
             by_owner_id: contract.by_owner_id,
             by_contract_id: contract.by_contract_id,
             storage_deposit: contract.storage_deposit,
-            sales
+            sales,
+            next_offer_id: 0,
+            offers: UnorderedMap::new(StorageKey::OfferKey.try_to_vec().unwrap()),
+            offers_by_token: LookupMap::new(StorageKey::OffersByTokenKey.try_to_vec().unwrap()),
+            offers_by_buyer: LookupMap::new(StorageKey::OffersByBuyerKey.try_to_vec().unwrap()),
+            collection_offers: UnorderedMap::new(StorageKey::CollectionOfferKey.try_to_vec().unwrap()),
+            collection_offers_by_contract_id: LookupMap::new(StorageKey::CollectionOffersByContractIdKey.try_to_vec().unwrap()),
+            collection_offers_by_buyer: LookupMap::new(StorageKey::CollectionOffersByBuyerKey.try_to_vec().unwrap()),
+            market_fee: 0,
+            accrued_fees: UnorderedMap::new(StorageKey::AccruedFeeKey.try_to_vec().unwrap()),
+            market_fee_by_contract_id: UnorderedMap::new(StorageKey::MarketFeeByContractIdKey.try_to_vec().unwrap()),
+            market_fee_by_seller: UnorderedMap::new(StorageKey::MarketFeeBySellerKey.try_to_vec().unwrap()),
+            ft_tokens: UnorderedMap::new(StorageKey::FTTokenKey.try_to_vec().unwrap()),
+            balances: LookupMap::new(StorageKey::BalanceKey.try_to_vec().unwrap()),
+            vouchers: UnorderedMap::new(StorageKey::VoucherKey.try_to_vec().unwrap()),
+            vouchers_by_creator: LookupMap::new(StorageKey::VouchersByCreatorKey.try_to_vec().unwrap())
         }
     }
 }
@@ -115,7 +245,35 @@ pub enum StorageKey {
     InnerByContractIdKey {
         account_id_hash: CryptoHash
     },
-    StorageDepositKey
+    StorageDepositKey,
+    OfferKey,
+    OffersByTokenKey,
+    InnerOffersByTokenKey {
+        contract_and_token_id_hash: CryptoHash
+    },
+    OffersByBuyerKey,
+    InnerOffersByBuyerKey {
+        account_id_hash: CryptoHash
+    },
+    CollectionOfferKey,
+    CollectionOffersByContractIdKey,
+    InnerCollectionOffersByContractIdKey {
+        account_id_hash: CryptoHash
+    },
+    CollectionOffersByBuyerKey,
+    InnerCollectionOffersByBuyerKey {
+        account_id_hash: CryptoHash
+    },
+    AccruedFeeKey,
+    MarketFeeByContractIdKey,
+    MarketFeeBySellerKey,
+    FTTokenKey,
+    BalanceKey,
+    VoucherKey,
+    VouchersByCreatorKey,
+    InnerVouchersByCreatorKey {
+        account_id_hash: CryptoHash
+    }
 }
 
 #[near_bindgen]
@@ -128,7 +286,22 @@ impl Contract {
             sales: UnorderedMap::new(StorageKey::SaleKey.try_to_vec().unwrap()),
             by_owner_id: LookupMap::new(StorageKey::ByOwnerIdKey.try_to_vec().unwrap()),
             by_contract_id: LookupMap::new(StorageKey::ByContractIdKey.try_to_vec().unwrap()),
-            storage_deposit: LookupMap::new(StorageKey::StorageDepositKey.try_to_vec().unwrap())
+            storage_deposit: LookupMap::new(StorageKey::StorageDepositKey.try_to_vec().unwrap()),
+            next_offer_id: 0,
+            offers: UnorderedMap::new(StorageKey::OfferKey.try_to_vec().unwrap()),
+            offers_by_token: LookupMap::new(StorageKey::OffersByTokenKey.try_to_vec().unwrap()),
+            offers_by_buyer: LookupMap::new(StorageKey::OffersByBuyerKey.try_to_vec().unwrap()),
+            collection_offers: UnorderedMap::new(StorageKey::CollectionOfferKey.try_to_vec().unwrap()),
+            collection_offers_by_contract_id: LookupMap::new(StorageKey::CollectionOffersByContractIdKey.try_to_vec().unwrap()),
+            collection_offers_by_buyer: LookupMap::new(StorageKey::CollectionOffersByBuyerKey.try_to_vec().unwrap()),
+            market_fee: 0,
+            accrued_fees: UnorderedMap::new(StorageKey::AccruedFeeKey.try_to_vec().unwrap()),
+            market_fee_by_contract_id: UnorderedMap::new(StorageKey::MarketFeeByContractIdKey.try_to_vec().unwrap()),
+            market_fee_by_seller: UnorderedMap::new(StorageKey::MarketFeeBySellerKey.try_to_vec().unwrap()),
+            ft_tokens: UnorderedMap::new(StorageKey::FTTokenKey.try_to_vec().unwrap()),
+            balances: LookupMap::new(StorageKey::BalanceKey.try_to_vec().unwrap()),
+            vouchers: UnorderedMap::new(StorageKey::VoucherKey.try_to_vec().unwrap()),
+            vouchers_by_creator: LookupMap::new(StorageKey::VouchersByCreatorKey.try_to_vec().unwrap())
         }
     }
 
@@ -151,11 +324,8 @@ impl Contract {
         let owner_id = env::predecessor_account_id();
 
         let amount = self.storage_deposit.remove(&owner_id).unwrap_or(0);
-        let sales = self.by_owner_id.get(&owner_id);
-        
-        let len = sales.map(| s | s.len()).unwrap_or_default();
 
-        let storage_required = u128::from(len) * STORAGE_PER_SALE;
+        let storage_required = self.internal_storage_items(&owner_id) * STORAGE_PER_SALE;
 
         assert!(amount >= storage_required);
 
@@ -181,7 +351,860 @@ impl Contract {
     #[private]
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let old_data: ContractV1 = env::state_read().expect("Not read state");
+        let old_data: ContractV2 = env::state_read().expect("Not read state");
         Self::from(old_data)
     }
-}
\ No newline at end of file
+}
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    use near_sdk::test_utils::{VMContextBuilder, accounts, get_created_receipts};
+    use near_sdk::{testing_env};
+    use near_sdk::MockedBlockchain;
+    use near_sdk::json_types::{Base64VecU8, ValidAccountId};
+    use near_sdk::PromiseResult;
+    use std::convert::TryFrom;
+
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+    const ONE_DAY: u64 = 86_400_000_000_000;
+
+    fn get_context(predecessor: usize) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder.
+        current_account_id(accounts(0))
+        .signer_account_id(accounts(predecessor))
+        .predecessor_account_id(accounts(predecessor))
+        .attached_deposit(1);
+
+        builder
+    }
+
+    fn get_dutch_auction(floor_price: u128, step: Option<U64>) -> DutchAuction {
+        let mut floor_prices = HashMap::new();
+        floor_prices.insert(NEAR_CURRENCY.to_string(), U128(floor_price));
+
+        DutchAuction {
+            floor_price: floor_prices,
+            start_at: U64(ONE_DAY),
+            end_at: U64(2 * ONE_DAY),
+            step
+        }
+    }
+
+    #[test]
+    fn test_dutch_auction_price_at() {
+        let dutch_auction = get_dutch_auction(ONE_NEAR, None);
+        let currency = NEAR_CURRENCY.to_string();
+        let start_price = 11 * ONE_NEAR;
+
+        assert_eq!(dutch_auction.price_at(&currency, start_price, 0), start_price);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, ONE_DAY), start_price);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, ONE_DAY + ONE_DAY / 4), 17 * ONE_NEAR / 2);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, ONE_DAY + ONE_DAY / 2), 6 * ONE_NEAR);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, 2 * ONE_DAY), ONE_NEAR);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, 3 * ONE_DAY), ONE_NEAR);
+    }
+
+    #[test]
+    fn test_dutch_auction_price_at_no_overflow() {
+        // 10 NEAR * 1 ngày (ns) lớn hơn u128::MAX
+        let dutch_auction = get_dutch_auction(ONE_NEAR, None);
+        let currency = NEAR_CURRENCY.to_string();
+        let start_price = 11 * ONE_NEAR;
+
+        let price = dutch_auction.price_at(&currency, start_price, 2 * ONE_DAY - 1);
+        assert!(price > ONE_NEAR && price < start_price);
+
+        let max_price = u128::MAX;
+        let price = get_dutch_auction(0, None).price_at(&currency, max_price, ONE_DAY + ONE_DAY / 2);
+        assert_eq!(price, max_price - max_price / 2);
+    }
+
+    #[test]
+    fn test_dutch_auction_price_at_step() {
+        // Giá giảm mỗi 6 giờ
+        let dutch_auction = get_dutch_auction(ONE_NEAR, Some(U64(ONE_DAY / 4)));
+        let currency = NEAR_CURRENCY.to_string();
+        let start_price = 5 * ONE_NEAR;
+
+        assert_eq!(dutch_auction.price_at(&currency, start_price, ONE_DAY + ONE_DAY / 4 - 1), start_price);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, ONE_DAY + ONE_DAY / 4), 4 * ONE_NEAR);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, ONE_DAY + ONE_DAY / 2 + 1), 3 * ONE_NEAR);
+        assert_eq!(dutch_auction.price_at(&currency, start_price, 2 * ONE_DAY - 1), 2 * ONE_NEAR);
+    }
+
+    fn list_auction(contract: &mut Contract, owner_id: AccountId, extension: Option<U64>) {
+        contract.internal_list_token(accounts(1).to_string(), "1".to_string(), owner_id, 0, ApprovalArgs::Auction(AuctionArgs {
+            auction_conditions: AuctionConditions {
+                reserve_price: U128(ONE_NEAR),
+                start_at: None,
+                end_at: U64(ONE_DAY),
+                min_bid_increment: U128(ONE_NEAR / 10),
+                extension
+            }
+        }));
+    }
+
+    fn list_sale(contract: &mut Contract, owner_id: AccountId, token_id: &str, price: u128, expires_at: Option<U64>) {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(NEAR_CURRENCY.to_string(), U128(price));
+
+        contract.internal_list_token(accounts(1).to_string(), token_id.to_string(), owner_id, 0, ApprovalArgs::Sale(SaleArgs {
+            sale_conditions,
+            dutch_auction: None,
+            expires_at
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than or equal minimum bid")]
+    fn test_place_bid_min_increment() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        list_auction(&mut contract, accounts(2).to_string(), None);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).attached_deposit(ONE_NEAR).build());
+        contract.place_bid(accounts(1).to_string(), "1".to_string());
+
+        // Bid mới phải lớn hơn bid cao nhất ít nhất min_bid_increment
+        testing_env!(context.predecessor_account_id(accounts(4)).attached_deposit(ONE_NEAR + ONE_NEAR / 20).build());
+        contract.place_bid(accounts(1).to_string(), "1".to_string());
+    }
+
+    #[test]
+    fn test_place_bid_extends_auction() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let extension = ONE_DAY / 24;
+        list_auction(&mut contract, accounts(2).to_string(), Some(U64(extension)));
+        let contract_and_token_id = format!("{}{}{}", accounts(1), ".", "1");
+
+        // Bid ngoài snipe window không gia hạn
+        testing_env!(context.predecessor_account_id(accounts(3)).attached_deposit(ONE_NEAR).build());
+        contract.place_bid(accounts(1).to_string(), "1".to_string());
+        let auction = contract.sales.get(&contract_and_token_id).unwrap().auction.unwrap();
+        assert_eq!(auction.end_at.0, ONE_DAY);
+
+        // Bid trong snipe window gia hạn end_at thêm 1 window tính từ lúc bid
+        let now = ONE_DAY - extension / 2;
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(2 * ONE_NEAR)
+            .block_timestamp(now)
+            .build());
+        contract.place_bid(accounts(1).to_string(), "1".to_string());
+        let auction = contract.sales.get(&contract_and_token_id).unwrap().auction.unwrap();
+        assert_eq!(auction.end_at.0, now + extension);
+        assert_eq!(auction.highest_bid.unwrap().bidder_id, accounts(4).to_string());
+    }
+
+    #[test]
+    fn test_outbid_refund_credits_balance() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        list_auction(&mut contract, accounts(2).to_string(), None);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).attached_deposit(ONE_NEAR).build());
+        contract.place_bid(accounts(1).to_string(), "1".to_string());
+
+        // Bid cũ được cộng vào số dư thay vì chuyển trực tiếp, bidder không có storage deposit nên storage trừ vào số dư
+        testing_env!(context.predecessor_account_id(accounts(4)).attached_deposit(2 * ONE_NEAR).build());
+        contract.place_bid(accounts(1).to_string(), "1".to_string());
+        assert_eq!(contract.get_balances(accounts(3).to_string()), vec![(NEAR_CURRENCY.to_string(), U128(ONE_NEAR - STORAGE_PER_BALANCE))]);
+        assert!(get_created_receipts().is_empty());
+
+        // Sale bị xoá do revoke approval, bid cao nhất cũng được cộng vào số dư
+        testing_env!(context.predecessor_account_id(accounts(1)).attached_deposit(0).build());
+        contract.nft_on_revoke("1".to_string(), 0);
+        assert_eq!(contract.get_supply_sales().0, 0);
+        assert_eq!(contract.get_balances(accounts(4).to_string()), vec![(NEAR_CURRENCY.to_string(), U128(2 * ONE_NEAR - STORAGE_PER_BALANCE))]);
+
+        // Rút hết thì storage của entry được trả lại vào storage deposit
+        testing_env!(context.predecessor_account_id(accounts(3)).attached_deposit(1).build());
+        assert_eq!(contract.withdraw(None).0, ONE_NEAR - STORAGE_PER_BALANCE);
+        assert_eq!(contract.storage_balance_of(accounts(3).to_string()).0, STORAGE_PER_BALANCE);
+    }
+
+    #[test]
+    fn test_settle_auction() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        list_auction(&mut contract, accounts(2).to_string(), None);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).attached_deposit(ONE_NEAR).build());
+        contract.place_bid(accounts(1).to_string(), "1".to_string());
+
+        // Ai cũng có thể settle sau khi đấu giá kết thúc, token được chuyển cho người trả giá cao nhất
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(0)
+            .block_timestamp(ONE_DAY)
+            .build());
+        contract.settle_auction(accounts(1).to_string(), "1".to_string());
+
+        assert_eq!(contract.get_supply_sales().0, 0);
+        let receipts = near_sdk::serde_json::to_string(&get_created_receipts()).unwrap();
+        assert!(receipts.contains("nft_transfer_payout"));
+        assert!(receipts.contains("resolve_purchase"));
+    }
+
+    #[test]
+    fn test_offer_refunds_excess() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        list_sale(&mut contract, accounts(2).to_string(), "1", ONE_NEAR, None);
+
+        // Chỉ thu đúng giá niêm yết, phần thừa hoàn lại cho buyer
+        testing_env!(context.predecessor_account_id(accounts(3)).attached_deposit(3 * ONE_NEAR).build());
+        contract.offer(accounts(1).to_string(), "1".to_string(), Some(U128(ONE_NEAR)));
+
+        assert_eq!(contract.get_supply_sales().0, 0);
+        let receipts = near_sdk::serde_json::to_string(&get_created_receipts()).unwrap();
+        assert!(receipts.contains(&format!(r#""deposit":{}"#, 2 * ONE_NEAR)));
+        assert!(receipts.contains("nft_transfer_payout"));
+    }
+
+    #[test]
+    #[should_panic(expected = "is greater than max_price")]
+    fn test_offer_max_price() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        list_sale(&mut contract, accounts(2).to_string(), "1", 2 * ONE_NEAR, None);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).attached_deposit(3 * ONE_NEAR).build());
+        contract.offer(accounts(1).to_string(), "1".to_string(), Some(U128(ONE_NEAR)));
+    }
+
+    #[test]
+    fn test_nft_on_batch_approve() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let owner_id = accounts(2).to_string();
+
+        testing_env!(context.attached_deposit(2 * STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        // Các token trong batch dùng chung sale conditions
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        let msg = r#"{"sale_conditions":{"near":"1000000000000000000000000"}}"#.to_string();
+        contract.nft_on_batch_approve(vec![("1".to_string(), 0), ("2".to_string(), 1)], owner_id.clone(), msg);
+
+        let sales = contract.get_sales_by_owner_id(owner_id.clone(), None, Some(10));
+        assert_eq!(sales.len(), 2);
+        assert_eq!(contract.sales.get(&format!("{}{}{}", accounts(1), ".", "2")).unwrap().approval_id, 1);
+        assert_eq!(contract.get_current_price(accounts(1).to_string(), "1".to_string()).get(NEAR_CURRENCY).unwrap().0, ONE_NEAR);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.remove_sales(accounts(1).to_string(), vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(contract.get_supply_by_owner_id(owner_id).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Storage balance not enough for cover storage staking")]
+    fn test_nft_on_batch_approve_storage() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        // Storage deposit chỉ đủ cho 1 sale
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        let msg = r#"{"sale_conditions":{"near":"1000000000000000000000000"}}"#.to_string();
+        contract.nft_on_batch_approve(vec![("1".to_string(), 0), ("2".to_string(), 1)], accounts(2).to_string(), msg);
+    }
+
+    #[test]
+    fn test_remove_expired_sales() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let seller_id = accounts(2).to_string();
+
+        testing_env!(context.attached_deposit(3 * STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        list_sale(&mut contract, seller_id.clone(), "1", ONE_NEAR, Some(U64(ONE_DAY)));
+        list_sale(&mut contract, seller_id.clone(), "2", ONE_NEAR, None);
+
+        // Sale hết hạn không hiện trong view nhưng vẫn chiếm storage cho tới khi bị xoá
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(0)
+            .block_timestamp(ONE_DAY)
+            .build());
+        assert_eq!(contract.get_sales(None, Some(10)).len(), 1);
+        assert_eq!(contract.get_supply_sales().0, 2);
+
+        // Chỉ sale hết hạn bị xoá, bounty lấy từ phần storage deposit không còn dùng của seller
+        let bounty = contract.remove_expired_sales(vec![
+            (accounts(1).to_string(), "1".to_string()),
+            (accounts(1).to_string(), "2".to_string())
+        ]);
+        assert_eq!(bounty.0, EXPIRED_SALE_BOUNTY);
+        assert_eq!(contract.get_supply_sales().0, 1);
+        assert_eq!(contract.storage_balance_of(seller_id).0, 3 * STORAGE_PER_SALE - EXPIRED_SALE_BOUNTY);
+    }
+
+    fn list_dutch_auction(contract: &mut Contract, owner_id: AccountId) {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(NEAR_CURRENCY.to_string(), U128(2 * ONE_NEAR));
+
+        contract.internal_list_token(accounts(1).to_string(), "1".to_string(), owner_id, 0, ApprovalArgs::Sale(SaleArgs {
+            sale_conditions,
+            dutch_auction: Some(get_dutch_auction(ONE_NEAR, None)),
+            expires_at: None
+        }));
+    }
+
+    #[test]
+    fn test_update_price_dutch_auction() {
+        let mut context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let ft_contract_id = accounts(4).to_string();
+        contract.add_ft_token(ft_contract_id.clone(), 18, "FT".to_string());
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        list_dutch_auction(&mut contract, accounts(2).to_string());
+
+        // Thêm currency cho dutch auction cần kèm giá sàn của currency đó
+        contract.update_price(accounts(1).to_string(), "1".to_string(), ft_contract_id.clone(), Some(U128(10)), Some(U128(5)));
+        let prices = contract.get_current_price(accounts(1).to_string(), "1".to_string());
+        assert_eq!(prices.get(&ft_contract_id).unwrap().0, 10);
+
+        testing_env!(context.block_timestamp(3 * ONE_DAY).build());
+        let prices = contract.get_current_price(accounts(1).to_string(), "1".to_string());
+        assert_eq!(prices.get(&ft_contract_id).unwrap().0, 5);
+        assert_eq!(prices.get(NEAR_CURRENCY).unwrap().0, ONE_NEAR);
+
+        // Bỏ currency thì giá sàn cũng bị bỏ
+        contract.update_price(accounts(1).to_string(), "1".to_string(), ft_contract_id.clone(), None, None);
+        let sale = contract.sales.get(&format!("{}{}{}", accounts(1), ".", "1")).unwrap();
+        assert!(!sale.dutch_auction.unwrap().floor_price.contains_key(&ft_contract_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing floor_price for currency")]
+    fn test_update_price_dutch_auction_without_floor_price() {
+        let mut context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let ft_contract_id = accounts(4).to_string();
+        contract.add_ft_token(ft_contract_id.clone(), 18, "FT".to_string());
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        list_dutch_auction(&mut contract, accounts(2).to_string());
+        contract.update_price(accounts(1).to_string(), "1".to_string(), ft_contract_id, Some(U128(10)), None);
+    }
+
+    #[test]
+    fn test_market_fee() {
+        let context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let nft_contract_id = accounts(1).to_string();
+        let seller_id = accounts(2).to_string();
+
+        contract.set_market_fee(250);
+        assert_eq!(contract.internal_market_fee(&nft_contract_id, &seller_id, 10_000), 250);
+
+        // Phí riêng của seller được ưu tiên hơn phí của nft contract
+        contract.set_contract_market_fee(nft_contract_id.clone(), Some(100));
+        assert_eq!(contract.get_effective_market_fee(nft_contract_id.clone(), seller_id.clone()), 100);
+        contract.set_seller_market_fee(seller_id.clone(), Some(0));
+        assert_eq!(contract.get_effective_market_fee(nft_contract_id.clone(), seller_id.clone()), 0);
+
+        contract.set_seller_market_fee(seller_id.clone(), None);
+        assert_eq!(contract.internal_market_fee(&nft_contract_id, &seller_id, u128::MAX), u128::MAX / 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Market fee can not be greater than 1000")]
+    fn test_market_fee_cap() {
+        let context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        contract.set_market_fee(MAX_MARKET_FEE + 1);
+    }
+
+    #[test]
+    fn test_ft_tokens() {
+        let context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let ft_contract_id = accounts(4).to_string();
+
+        contract.add_ft_token(ft_contract_id.clone(), 18, "FT".to_string());
+        assert_eq!(contract.get_ft_token(ft_contract_id.clone()).unwrap().decimals, 18);
+        assert_eq!(contract.get_ft_tokens(None, Some(10)).len(), 1);
+
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_contract_id.clone(), U128(10));
+        contract.internal_list_token(accounts(1).to_string(), "1".to_string(), accounts(2).to_string(), 0, ApprovalArgs::Sale(SaleArgs {
+            sale_conditions,
+            dutch_auction: None,
+            expires_at: None
+        }));
+
+        // Xoá FT không xoá sale đang dùng FT đó
+        contract.remove_ft_token(ft_contract_id.clone());
+        assert!(contract.get_ft_token(ft_contract_id).is_none());
+        assert_eq!(contract.get_supply_sales().0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not accepted by market")]
+    fn test_list_sale_with_removed_ft_token() {
+        let context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let ft_contract_id = accounts(4).to_string();
+        contract.add_ft_token(ft_contract_id.clone(), 18, "FT".to_string());
+        contract.remove_ft_token(ft_contract_id.clone());
+
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_contract_id, U128(10));
+        contract.internal_list_token(accounts(1).to_string(), "1".to_string(), accounts(2).to_string(), 0, ApprovalArgs::Sale(SaleArgs {
+            sale_conditions,
+            dutch_auction: None,
+            expires_at: None
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only contract owner can call this method")]
+    fn test_add_ft_token_not_owner() {
+        let context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        contract.add_ft_token(accounts(4).to_string(), 18, "FT".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Native currency can not be paid with ft_on_transfer")]
+    fn test_ft_on_transfer_from_near_account() {
+        let mut context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+
+        testing_env!(context
+            .predecessor_account_id(ValidAccountId::try_from(NEAR_CURRENCY).unwrap())
+            .attached_deposit(0)
+            .build());
+        let msg = format!(r#"{{"nft_contract_id":"{}","token_id":"1"}}"#, accounts(1));
+        contract.ft_on_transfer(accounts(2).to_string(), U128(ONE_NEAR), msg);
+    }
+
+    #[test]
+    fn test_accept_offer() {
+        let mut context = get_context(3);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        testing_env!(context.attached_deposit(ONE_NEAR).build());
+        let offer_id = contract.make_offer(accounts(1).to_string(), "1".to_string(), None);
+        assert_eq!(contract.get_offers_by_token(accounts(1).to_string(), "1".to_string(), None, Some(10)).len(), 1);
+
+        // Owner chấp nhận offer qua nft_approve trên NFT contract
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        let msg = format!(r#"{{"offer_id":{}}}"#, offer_id);
+        contract.nft_on_approve("1".to_string(), accounts(2).to_string(), 0, msg);
+
+        assert!(contract.get_offer(offer_id).is_none());
+        assert!(contract.get_offers_by_buyer(accounts(3).to_string(), None, Some(10)).is_empty());
+        let receipts = near_sdk::serde_json::to_string(&get_created_receipts()).unwrap();
+        assert!(receipts.contains("nft_transfer_payout"));
+    }
+
+    #[test]
+    fn test_cancel_expired_offer() {
+        let mut context = get_context(3);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let buyer_id = accounts(3).to_string();
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        testing_env!(context.attached_deposit(ONE_NEAR).build());
+        let offer_id = contract.make_offer(accounts(1).to_string(), "1".to_string(), Some(U64(ONE_DAY)));
+
+        // Offer hết hạn thì account bất kỳ có thể huỷ, tiền được hoàn vào số dư của buyer
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(1)
+            .block_timestamp(ONE_DAY)
+            .build());
+        contract.cancel_offer(offer_id);
+
+        assert!(contract.get_offer(offer_id).is_none());
+        assert_eq!(contract.get_balance(buyer_id, NEAR_CURRENCY.to_string()).0, ONE_NEAR);
+        assert_eq!(contract.get_balance(accounts(5).to_string(), NEAR_CURRENCY.to_string()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only buyer can cancel an active offer")]
+    fn test_cancel_active_offer_not_buyer() {
+        let mut context = get_context(3);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        testing_env!(context.attached_deposit(ONE_NEAR).build());
+        let offer_id = contract.make_offer(accounts(1).to_string(), "1".to_string(), Some(U64(ONE_DAY)));
+
+        testing_env!(context.predecessor_account_id(accounts(5)).attached_deposit(1).build());
+        contract.cancel_offer(offer_id);
+    }
+
+    #[test]
+    fn test_accept_collection_offer() {
+        let mut context = get_context(3);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let buyer_id = accounts(3).to_string();
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        testing_env!(context.attached_deposit(2 * ONE_NEAR).build());
+        let offer_id = contract.make_collection_offer(accounts(1).to_string(), U128(ONE_NEAR), 2, None);
+
+        // Mỗi token được chấp nhận trừ 1 quantity, hết quantity thì offer bị xoá
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        let msg = format!(r#"{{"collection_offer_id":{}}}"#, offer_id);
+        contract.nft_on_approve("1".to_string(), accounts(2).to_string(), 0, msg.clone());
+        assert_eq!(contract.get_collection_offer(offer_id).unwrap().quantity, 1);
+
+        contract.nft_on_approve("2".to_string(), accounts(2).to_string(), 0, msg);
+        assert!(contract.get_collection_offer(offer_id).is_none());
+        assert!(contract.get_collection_offers_by_buyer(buyer_id, None, Some(10)).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_collection_offer_refunds_remaining() {
+        let mut context = get_context(3);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let buyer_id = accounts(3).to_string();
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        testing_env!(context.attached_deposit(3 * ONE_NEAR).build());
+        let offer_id = contract.make_collection_offer(accounts(1).to_string(), U128(ONE_NEAR), 3, Some(U64(ONE_DAY)));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        let msg = format!(r#"{{"collection_offer_id":{}}}"#, offer_id);
+        contract.nft_on_approve("1".to_string(), accounts(2).to_string(), 0, msg);
+
+        // Offer hết hạn, account bất kỳ huỷ và buyer nhận lại tiền của 2 token chưa mua
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .signer_account_id(accounts(5))
+            .attached_deposit(1)
+            .block_timestamp(ONE_DAY)
+            .build());
+        contract.cancel_collection_offer(offer_id);
+        assert_eq!(contract.get_balance(buyer_id, NEAR_CURRENCY.to_string()).0, 2 * ONE_NEAR);
+    }
+
+    #[test]
+    fn test_credit_below_storage_cost() {
+        let mut context = get_context(0);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+
+        // Seller có storage deposit nên nhận đủ, các account payout nhỏ hơn phí storage bị từ chối
+        testing_env!(context.predecessor_account_id(accounts(2)).attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        let mut payout = HashMap::new();
+        payout.insert(accounts(2).to_string(), U128(ONE_NEAR));
+        payout.insert(accounts(4).to_string(), U128(1));
+        payout.insert(accounts(5).to_string(), U128(STORAGE_PER_BALANCE));
+        let purchase = Purchase {
+            nft_contract_id: accounts(1).to_string(),
+            token_id: "1".to_string(),
+            seller_id: accounts(2).to_string(),
+            buyer_id: accounts(3).to_string(),
+            currency: NEAR_CURRENCY.to_string(),
+            price: U128(ONE_NEAR + 1 + STORAGE_PER_BALANCE),
+            market_fee: U128(0)
+        };
+
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).attached_deposit(0).build(),
+            Default::default(),
+            Default::default(),
+            HashMap::default(),
+            vec![PromiseResult::Successful(near_sdk::serde_json::to_vec(&Payout { payout }).unwrap())]
+        );
+        contract.resolve_purchase(purchase);
+
+        assert_eq!(contract.get_balance(accounts(2).to_string(), NEAR_CURRENCY.to_string()).0, ONE_NEAR);
+        assert_eq!(contract.storage_balance_of(accounts(2).to_string()).0, STORAGE_PER_SALE - STORAGE_PER_BALANCE);
+        assert!(contract.get_balances(accounts(4).to_string()).is_empty());
+        assert!(contract.get_balances(accounts(5).to_string()).is_empty());
+        assert_eq!(contract.get_accrued_fee(NEAR_CURRENCY.to_string()).0, 1 + STORAGE_PER_BALANCE);
+    }
+
+    #[test]
+    fn test_cancel_offer_credits_balance() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let buyer_id = accounts(2).to_string();
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        testing_env!(context.attached_deposit(ONE_NEAR).build());
+        let offer_id = contract.make_offer(accounts(1).to_string(), "1".to_string(), None);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.cancel_offer(offer_id);
+        assert_eq!(contract.get_balance(buyer_id.clone(), NEAR_CURRENCY.to_string()).0, ONE_NEAR);
+        assert_eq!(contract.get_balances(buyer_id.clone()).len(), 1);
+
+        assert_eq!(contract.withdraw(Some(U128(ONE_NEAR / 4))).0, ONE_NEAR / 4);
+        assert_eq!(contract.get_balance(buyer_id.clone(), NEAR_CURRENCY.to_string()).0, 3 * ONE_NEAR / 4);
+
+        contract.withdraw(None);
+        assert!(contract.get_balances(buyer_id).is_empty());
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let owner_id = accounts(2).to_string();
+        let nft_contract_id = accounts(1).to_string();
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", "1");
+
+        let mut old_contract = ContractV2 {
+            owner_id: accounts(0).to_string(),
+            sales: UnorderedMap::new(StorageKey::SaleKey.try_to_vec().unwrap()),
+            by_owner_id: LookupMap::new(StorageKey::ByOwnerIdKey.try_to_vec().unwrap()),
+            by_contract_id: LookupMap::new(StorageKey::ByContractIdKey.try_to_vec().unwrap()),
+            storage_deposit: LookupMap::new(StorageKey::StorageDepositKey.try_to_vec().unwrap())
+        };
+        old_contract.sales.insert(&contract_and_token_id, &SaleV2 {
+            owner_id: owner_id.clone(),
+            approval_id: 0,
+            nft_contract_id: nft_contract_id.clone(),
+            token_id: "1".to_string(),
+            sale_conditions: SalePriceV2 {
+                is_native: true,
+                contract_id: NEAR_CURRENCY.to_string(),
+                decimals: U64(24),
+                amount: U128(ONE_NEAR)
+            }
+        });
+        let mut by_owner_id = UnorderedSet::new(
+            StorageKey::InnterByOwnerIdKey { account_id_hash: hash_account_id(&owner_id) }.try_to_vec().unwrap()
+        );
+        by_owner_id.insert(&contract_and_token_id);
+        old_contract.by_owner_id.insert(&owner_id, &by_owner_id);
+        let mut by_contract_id = UnorderedSet::new(
+            StorageKey::InnerByContractIdKey { account_id_hash: hash_account_id(&nft_contract_id) }.try_to_vec().unwrap()
+        );
+        by_contract_id.insert(&"1".to_string());
+        old_contract.by_contract_id.insert(&nft_contract_id, &by_contract_id);
+        old_contract.storage_deposit.insert(&owner_id, &(2 * STORAGE_PER_SALE));
+        env::state_write(&old_contract);
+
+        let mut contract = Contract::migrate();
+        let sale = contract.sales.get(&contract_and_token_id).unwrap();
+        assert_eq!(sale.sale_conditions.get(NEAR_CURRENCY).unwrap().0, ONE_NEAR);
+        assert!(sale.auction.is_none());
+        assert_eq!(contract.get_sales_by_owner_id(owner_id.clone(), None, Some(10)).len(), 1);
+
+        // Đăng lại token đã đăng bán trước khi migrate
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(NEAR_CURRENCY.to_string(), U128(2 * ONE_NEAR));
+        testing_env!(context.attached_deposit(0).build());
+        contract.internal_list_token(nft_contract_id.clone(), "1".to_string(), owner_id.clone(), 1, ApprovalArgs::Sale(SaleArgs {
+            sale_conditions,
+            dutch_auction: None,
+            expires_at: None
+        }));
+
+        let sale = contract.sales.get(&contract_and_token_id).unwrap();
+        assert_eq!(sale.approval_id, 1);
+        assert_eq!(sale.sale_conditions.get(NEAR_CURRENCY).unwrap().0, 2 * ONE_NEAR);
+        assert_eq!(contract.get_supply_sales().0, 1);
+        assert_eq!(contract.internal_storage_items(&owner_id), 1);
+    }
+
+    fn get_voucher_listing(creator_id: AccountId, price: u128) -> VoucherListing {
+        VoucherListing {
+            nft_contract_id: accounts(1).to_string(),
+            voucher: MintVoucher {
+                token_id: "lazy_nft".to_string(),
+                creator_id,
+                metadata_hash: Base64VecU8(vec![0; 32]),
+                price: U128(price),
+                royalty: HashMap::new(),
+                expires_at: None,
+                market_id: Some(accounts(0).to_string())
+            },
+            signature: Base64VecU8(vec![0; 64])
+        }
+    }
+
+    #[test]
+    fn test_buy_voucher() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let creator_id = accounts(2).to_string();
+        let buyer_id = accounts(3).to_string();
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        let listing = get_voucher_listing(creator_id.clone(), ONE_NEAR);
+        contract.list_voucher(listing.nft_contract_id, listing.voucher, listing.signature);
+        assert_eq!(contract.get_vouchers_by_creator(creator_id.clone(), None, Some(10)).len(), 1);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(2 * ONE_NEAR)
+            .build());
+        contract.buy_voucher(accounts(1).to_string(), "lazy_nft".to_string(), near_sdk::serde_json::json!({}));
+        assert!(contract.get_voucher(accounts(1).to_string(), "lazy_nft".to_string()).is_none());
+
+        let purchase = Purchase {
+            nft_contract_id: accounts(1).to_string(),
+            token_id: "lazy_nft".to_string(),
+            seller_id: creator_id.clone(),
+            buyer_id: buyer_id.clone(),
+            currency: NEAR_CURRENCY.to_string(),
+            price: U128(ONE_NEAR),
+            market_fee: U128(ONE_NEAR / 100)
+        };
+
+        // Creator chỉ nhận price trừ phí market, không phụ thuộc giá trị NFT contract trả về
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).attached_deposit(0).build(),
+            Default::default(),
+            Default::default(),
+            HashMap::default(),
+            vec![PromiseResult::Successful(near_sdk::serde_json::to_vec(&U128(u128::MAX)).unwrap())]
+        );
+        assert!(contract.resolve_buy_voucher(purchase, U128(2 * ONE_NEAR)));
+        assert_eq!(contract.get_balance(creator_id, NEAR_CURRENCY.to_string()).0, ONE_NEAR - ONE_NEAR / 100);
+        assert_eq!(contract.get_balance(buyer_id, NEAR_CURRENCY.to_string()).0, 0);
+        assert_eq!(contract.get_accrued_fee(NEAR_CURRENCY.to_string()).0, ONE_NEAR / 100);
+    }
+
+    #[test]
+    fn test_buy_voucher_failed() {
+        let mut context = get_context(2);
+        testing_env!(context.build());
+
+        let mut contract = Contract::new(accounts(0).to_string());
+        let creator_id = accounts(2).to_string();
+        let buyer_id = accounts(3).to_string();
+
+        testing_env!(context.attached_deposit(STORAGE_PER_SALE).build());
+        contract.storage_deposit(None);
+
+        let listing = get_voucher_listing(creator_id.clone(), ONE_NEAR);
+        contract.list_voucher(listing.nft_contract_id, listing.voucher, listing.signature);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(2 * ONE_NEAR)
+            .build());
+        contract.buy_voucher(accounts(1).to_string(), "lazy_nft".to_string(), near_sdk::serde_json::json!({}));
+
+        let purchase = Purchase {
+            nft_contract_id: accounts(1).to_string(),
+            token_id: "lazy_nft".to_string(),
+            seller_id: creator_id.clone(),
+            buyer_id: buyer_id.clone(),
+            currency: NEAR_CURRENCY.to_string(),
+            price: U128(ONE_NEAR),
+            market_fee: U128(0)
+        };
+
+        // Mint lỗi thì buyer được hoàn toàn bộ deposit (trừ storage của entry số dư) và voucher không được đăng lại
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).attached_deposit(0).build(),
+            Default::default(),
+            Default::default(),
+            HashMap::default(),
+            vec![PromiseResult::Failed]
+        );
+        assert!(!contract.resolve_buy_voucher(purchase, U128(2 * ONE_NEAR)));
+        assert_eq!(contract.get_balance(buyer_id, NEAR_CURRENCY.to_string()).0, 2 * ONE_NEAR - STORAGE_PER_BALANCE);
+        assert_eq!(contract.get_balance(creator_id, NEAR_CURRENCY.to_string()).0, 0);
+        assert!(contract.get_voucher(accounts(1).to_string(), "lazy_nft".to_string()).is_none());
+    }
+}
@@ -2,13 +2,40 @@ use crate::*;
 
 pub trait NonFungibleTokenApprovalReceiver {
     fn nft_on_approve(&mut self, token_id: TokenId, owner_id: AccountId, approval_id: u64, msg: String);
+    fn nft_on_batch_approve(&mut self, tokens: Vec<(TokenId, u64)>, owner_id: AccountId, msg: String);
+    fn nft_on_revoke(&mut self, token_id: TokenId, approval_id: u64);
 }
 
 
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct SaleArgs {
-    pub sale_conditions: SalePrice
+    pub sale_conditions: SaleConditions,
+    pub dutch_auction: Option<DutchAuction>,
+    pub expires_at: Option<U64>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AcceptOfferArgs {
+    pub offer_id: OfferId
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AcceptCollectionOfferArgs {
+    pub collection_offer_id: OfferId
+}
+
+// msg của nft_approve: đăng bán giá cố định, đấu giá hoặc chấp nhận offer
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+pub enum ApprovalArgs {
+    Sale(SaleArgs),
+    Auction(AuctionArgs),
+    AcceptOffer(AcceptOfferArgs),
+    AcceptCollectionOffer(AcceptCollectionOfferArgs)
 }
 
 
@@ -24,47 +51,122 @@ impl NonFungibleTokenApprovalReceiver for Contract {
         assert_ne!(nft_contract_id, signer_id, "nft_on_approve should only called via cross contract call");
         assert_eq!(signer_id, owner_id, "owner_id should be signer_id");
 
-        // Check cover storage
+        let args: ApprovalArgs = near_sdk::serde_json::from_str(&msg).expect("Not valid Sale Args");
+
+        match args {
+            ApprovalArgs::AcceptOffer(AcceptOfferArgs { offer_id }) => {
+                // Chủ token chấp nhận offer của buyer
+                self.internal_accept_offer(nft_contract_id, token_id, owner_id, approval_id, offer_id);
+            },
+            ApprovalArgs::AcceptCollectionOffer(AcceptCollectionOfferArgs { collection_offer_id }) => {
+                self.internal_accept_collection_offer(nft_contract_id, token_id, owner_id, approval_id, collection_offer_id);
+            },
+            _ => {
+                // Check cover storage
+                self.internal_assert_storage_available(&signer_id, 1);
+
+                self.internal_list_token(nft_contract_id, token_id, owner_id, approval_id, args);
+            }
+        }
+    }
 
-        let storage_balance = self.storage_deposit.get(&signer_id).unwrap_or(0);
-        let storage_minimum_amount = self.storage_minimum_balance().0;
-        let storage_required = (self.get_supply_by_owner_id(signer_id.clone()).0 + 1) * storage_minimum_amount;
+    // Đăng bán nhiều token cùng sale conditions, storage chỉ kiểm tra 1 lần cho cả batch
+    fn nft_on_batch_approve(&mut self, tokens: Vec<(TokenId, u64)>, owner_id: AccountId, msg: String) {
+        let nft_contract_id = env::predecessor_account_id();
+        let signer_id = env::signer_account_id();
 
-        assert!(storage_balance >= storage_required, "Storage balance not enough for cover storage staking");
+        assert_ne!(nft_contract_id, signer_id, "nft_on_batch_approve should only called via cross contract call");
+        assert_eq!(signer_id, owner_id, "owner_id should be signer_id");
 
-        let SaleArgs { sale_conditions } = near_sdk::serde_json::from_str(&msg).expect("Not valid Sale Args");
+        // Check cover storage
+        self.internal_assert_storage_available(&signer_id, tokens.len() as u128);
+
+        for (token_id, approval_id) in tokens {
+            let args: ApprovalArgs = near_sdk::serde_json::from_str(&msg).expect("Not valid Sale Args");
+
+            match args {
+                ApprovalArgs::Sale(_) | ApprovalArgs::Auction(_) => {
+                    self.internal_list_token(nft_contract_id.clone(), token_id, owner_id.clone(), approval_id, args);
+                },
+                _ => env::panic("Batch approve only supports listing tokens".as_bytes())
+            }
+        }
+    }
+
+    // NFT contract thông báo market bị revoke approval => xoá sale
+    fn nft_on_revoke(&mut self, token_id: TokenId, approval_id: u64) {
+        let nft_contract_id = env::predecessor_account_id();
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+
+        // Chỉ xoá sale ứng với approval bị revoke
+        if let Some(sale) = self.sales.get(&contract_and_token_id) {
+            if sale.approval_id == approval_id {
+                self.internal_delist(nft_contract_id, token_id);
+            }
+        }
+    }
+}
 
-        let contract_and_token_id = format!("{}{}{}", nft_contract_id.clone(), ".", token_id.clone());
+impl Contract {
+    pub(crate) fn internal_list_token(&mut self, nft_contract_id: AccountId, token_id: TokenId, owner_id: AccountId, approval_id: u64, args: ApprovalArgs) {
+        let (sale_conditions, auction, dutch_auction, expires_at) = match args {
+            ApprovalArgs::Sale(SaleArgs { sale_conditions, dutch_auction, expires_at }) => {
+                assert!(!sale_conditions.is_empty(), "Sale conditions must have at least one currency");
+                for currency in sale_conditions.keys() {
+                    self.internal_assert_currency_allowed(currency);
+                }
+                if let Some(dutch_auction) = &dutch_auction {
+                    dutch_auction.assert_valid(&sale_conditions);
+                }
+                if let Some(expires_at) = expires_at {
+                    assert!(expires_at.0 > env::block_timestamp(), "expires_at must be in the future");
+                }
+                (sale_conditions, None, dutch_auction, expires_at)
+            },
+            ApprovalArgs::Auction(AuctionArgs { auction_conditions }) => {
+                let auction = auction_conditions.into_auction();
+                let mut sale_conditions = HashMap::new();
+                sale_conditions.insert(NEAR_CURRENCY.to_string(), auction.reserve_price);
+                (
+                    sale_conditions,
+                    Some(auction),
+                    None,
+                    None
+                )
+            },
+            _ => env::panic("Not valid Sale Args".as_bytes())
+        };
 
         // Thêm sales collections
-        self.sales.insert(&contract_and_token_id, &Sale {
-            owner_id: owner_id.clone(),
+        self.internal_add_sale(Sale {
+            owner_id,
             approval_id,
-            nft_contract_id: nft_contract_id.clone(),
-            token_id: token_id.clone(),
-            sale_conditions
-        });
-
-        let mut by_owner_id = self.by_owner_id.get(&owner_id).unwrap_or_else(|| {
-            UnorderedSet::new(
-                StorageKey::InnterByOwnerIdKey {
-                    account_id_hash: hash_account_id(&owner_id)
-                }.try_to_vec().unwrap()
-            )
+            nft_contract_id,
+            token_id,
+            sale_conditions,
+            auction,
+            dutch_auction,
+            expires_at
         });
+    }
+}
 
-        by_owner_id.insert(&contract_and_token_id);
-        self.by_owner_id.insert(&owner_id, &by_owner_id);
-
-        let mut by_contract_id = self.by_contract_id.get(&nft_contract_id).unwrap_or_else(|| {
-            UnorderedSet::new(
-                StorageKey::InnerByContractIdKey {
-                    account_id_hash: hash_account_id(&nft_contract_id)
-                }.try_to_vec().unwrap()
-            )
-        });
+pub trait NonFungibleTokenTransferListener {
+    fn nft_on_token_transfer(&mut self, token_id: TokenId, approval_id: u64, old_owner_id: AccountId, new_owner_id: AccountId);
+}
 
-        by_contract_id.insert(&token_id);
-        self.by_contract_id.insert(&nft_contract_id, &by_contract_id);
+#[near_bindgen]
+impl NonFungibleTokenTransferListener for Contract {
+    // NFT contract thông báo token đã được transfer ngoài market => xoá sale
+    fn nft_on_token_transfer(&mut self, token_id: TokenId, approval_id: u64, old_owner_id: AccountId, new_owner_id: AccountId) {
+        let nft_contract_id = env::predecessor_account_id();
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+
+        if let Some(sale) = self.sales.get(&contract_and_token_id) {
+            if sale.approval_id == approval_id && sale.owner_id == old_owner_id {
+                log!("Remove sale {}: token transferred to {}", contract_and_token_id, new_owner_id);
+                self.internal_delist(nft_contract_id, token_id);
+            }
+        }
     }
-}
\ No newline at end of file
+}
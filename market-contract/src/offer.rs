@@ -0,0 +1,254 @@
+use crate::*;
+
+//GAS constants to attach to calls
+// resolve callback chỉ cộng số dư cho tối đa 10 account payout
+const GAS_FOR_ROYALTIES: Gas = 30_000_000_000_000;
+const GAS_FOR_NFT_TRANSFER: Gas = 15_000_000_000_000;
+//constant used to attach 0 NEAR to a call
+const NO_DEPOSIT: Balance = 0;
+
+// Offer của buyer cho 1 token bất kỳ, tiền (NEAR hoặc FT) được giữ bởi market
+#[derive(Deserialize, Serialize, BorshSerialize, BorshDeserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Offer {
+    pub offer_id: OfferId,
+    pub buyer_id: AccountId,
+    pub nft_contract_id: NFTContractId,
+    pub token_id: TokenId,
+    pub price: SalePrice,
+    pub expires_at: Option<U64> // nanoseconds
+}
+
+impl Offer {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map(| expires_at | env::block_timestamp() >= expires_at.0).unwrap_or(false)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OfferArgs {
+    pub nft_contract_id: AccountId,
+    pub token_id: TokenId,
+    pub expires_at: Option<U64>
+}
+
+#[near_bindgen]
+impl Contract {
+
+    #[payable]
+    pub fn make_offer(&mut self, nft_contract_id: AccountId, token_id: TokenId, expires_at: Option<U64>) -> OfferId {
+        let deposit = env::attached_deposit();
+        assert!(deposit > 0, "Attached deposit must be greater than 0");
+
+        self.internal_add_offer(env::predecessor_account_id(), nft_contract_id, token_id, SalePrice {
+            is_native: true,
+            contract_id: NEAR_CURRENCY.to_string(),
+            amount: U128(deposit)
+        }, expires_at)
+    }
+
+    // Buyer huỷ offer bất kỳ lúc nào, offer hết hạn thì ai cũng có thể huỷ để hoàn tiền cho buyer
+    #[payable]
+    pub fn cancel_offer(&mut self, offer_id: OfferId) {
+        assert_one_yocto();
+
+        let offer = self.internal_remove_offer(offer_id);
+        assert!(
+            env::predecessor_account_id() == offer.buyer_id || offer.is_expired(),
+            "Only buyer can cancel an active offer"
+        );
+
+        self.internal_refund(offer.buyer_id, &offer.price, offer.price.amount);
+    }
+
+    pub fn get_offer(&self, offer_id: OfferId) -> Option<Offer> {
+        self.offers.get(&offer_id)
+    }
+
+    pub fn get_offers_by_token(&self, nft_contract_id: AccountId, token_id: TokenId, from_index: Option<U128>, limit: Option<u64>) -> Vec<Offer> {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let offer_ids = if let Some(offer_ids) = self.offers_by_token.get(&contract_and_token_id) {
+            offer_ids
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        offer_ids.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .map(| offer_id | self.offers.get(&offer_id).unwrap())
+        .collect()
+    }
+
+    pub fn get_offers_by_buyer(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<Offer> {
+        let offer_ids = if let Some(offer_ids) = self.offers_by_buyer.get(&account_id) {
+            offer_ids
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        offer_ids.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .map(| offer_id | self.offers.get(&offer_id).unwrap())
+        .collect()
+    }
+
+    pub(crate) fn internal_add_offer(&mut self, buyer_id: AccountId, nft_contract_id: AccountId, token_id: TokenId, price: SalePrice, expires_at: Option<U64>) -> OfferId {
+        if let Some(expires_at) = expires_at {
+            assert!(expires_at.0 > env::block_timestamp(), "expires_at must be in the future");
+        }
+
+        // Check cover storage
+        self.internal_assert_storage_available(&buyer_id, 1);
+
+        let offer_id = self.next_offer_id;
+        self.next_offer_id += 1;
+
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+
+        let mut by_token = self.offers_by_token.get(&contract_and_token_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::InnerOffersByTokenKey {
+                    contract_and_token_id_hash: hash_account_id(&contract_and_token_id)
+                }.try_to_vec().unwrap()
+            )
+        });
+        by_token.insert(&offer_id);
+        self.offers_by_token.insert(&contract_and_token_id, &by_token);
+
+        let mut by_buyer = self.offers_by_buyer.get(&buyer_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::InnerOffersByBuyerKey {
+                    account_id_hash: hash_account_id(&buyer_id)
+                }.try_to_vec().unwrap()
+            )
+        });
+        by_buyer.insert(&offer_id);
+        self.offers_by_buyer.insert(&buyer_id, &by_buyer);
+
+        emit_event(EventLogVariant::OfferCreated(vec![ OfferLog {
+            offer_id,
+            buyer_id: buyer_id.clone(),
+            nft_contract_id: nft_contract_id.clone(),
+            token_id: Some(token_id.clone()),
+            currency: price.contract_id.clone(),
+            price: price.amount,
+            quantity: None,
+            expires_at
+        } ]));
+
+        self.offers.insert(&offer_id, &Offer {
+            offer_id,
+            buyer_id,
+            nft_contract_id,
+            token_id,
+            price,
+            expires_at
+        });
+
+        offer_id
+    }
+
+    pub(crate) fn internal_remove_offer(&mut self, offer_id: OfferId) -> Offer {
+        let offer = self.offers.remove(&offer_id).expect("Not found offer");
+        let contract_and_token_id = format!("{}{}{}", offer.nft_contract_id, ".", offer.token_id);
+
+        let mut by_token = self.offers_by_token.get(&contract_and_token_id).expect("Not found offer by token");
+        by_token.remove(&offer_id);
+        if by_token.is_empty() {
+            self.offers_by_token.remove(&contract_and_token_id);
+        } else {
+            self.offers_by_token.insert(&contract_and_token_id, &by_token);
+        }
+
+        let mut by_buyer = self.offers_by_buyer.get(&offer.buyer_id).expect("Not found offer by buyer");
+        by_buyer.remove(&offer_id);
+        if by_buyer.is_empty() {
+            self.offers_by_buyer.remove(&offer.buyer_id);
+        } else {
+            self.offers_by_buyer.insert(&offer.buyer_id, &by_buyer);
+        }
+
+        emit_event(EventLogVariant::OfferRemoved(vec![ OfferRemovedLog {
+            offer_id,
+            buyer_id: offer.buyer_id.clone()
+        } ]));
+
+        offer
+    }
+
+    // Hoàn lại tiền escrow (NEAR hoặc FT) vào số dư của buyer, buyer tự rút qua withdraw/ft_withdraw
+    pub(crate) fn internal_refund(&mut self, account_id: AccountId, price: &SalePrice, amount: U128) {
+        self.internal_credit(&account_id, &price.contract_id, amount.0);
+    }
+
+    pub(crate) fn internal_accept_offer(&mut self, nft_contract_id: AccountId, token_id: TokenId, owner_id: AccountId, approval_id: u64, offer_id: OfferId) {
+        let offer = self.internal_remove_offer(offer_id);
+
+        assert_eq!(offer.nft_contract_id, nft_contract_id, "Offer is not for this contract");
+        assert_eq!(offer.token_id, token_id, "Offer is not for this token");
+        assert!(!offer.is_expired(), "Offer has expired");
+        assert_ne!(offer.buyer_id, owner_id, "Can not accept your own offer");
+
+        self.internal_settle_offer(nft_contract_id, token_id, approval_id, owner_id, offer.buyer_id, offer.price);
+    }
+
+    // Chuyển token cho buyer và trả tiền offer cho seller + royalties
+    pub(crate) fn internal_settle_offer(&mut self, nft_contract_id: AccountId, token_id: TokenId, approval_id: u64, seller_id: AccountId, buyer_id: AccountId, price: SalePrice) {
+        // Token đang đăng bán thì xoá sale
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        if self.sales.get(&contract_and_token_id).is_some() {
+            let sale = self.internal_remove_sale(nft_contract_id.clone(), token_id.clone());
+            if let Some(auction) = sale.auction {
+                assert!(auction.highest_bid.is_none(), "Can not accept offer on an auction with active bids");
+            }
+        }
+
+        // Phí market được trừ trước khi chia cho seller và royalties
+        let market_fee = self.internal_market_fee(&nft_contract_id, &seller_id, price.amount.0);
+
+        let transfer_payout = ext_nft_contract::nft_transfer_payout(
+            buyer_id.clone(),
+            token_id.clone(),
+            approval_id,
+            "Payout from market contract".to_string(),
+            U128(price.amount.0 - market_fee),
+            10,
+            &nft_contract_id,
+            1,
+            GAS_FOR_NFT_TRANSFER
+        );
+
+        let purchase = Purchase {
+            nft_contract_id,
+            token_id,
+            seller_id,
+            buyer_id,
+            currency: price.contract_id,
+            price: price.amount,
+            market_fee: U128(market_fee)
+        };
+
+        if price.is_native {
+            transfer_payout.then(ext_self::resolve_purchase(
+                purchase,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_ROYALTIES
+            ));
+        } else {
+            transfer_payout.then(ext_self::ft_resolve_offer_purchase(
+                purchase,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_ROYALTIES
+            ));
+        }
+    }
+}
@@ -1,10 +1,18 @@
 use crate::*;
 use std::collections::HashMap;
 use near_sdk::promise_result_as_success;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde_json::Value;
 
 //GAS constants to attach to calls
-const GAS_FOR_ROYALTIES: Gas = 115_000_000_000_000;
+// resolve callback chỉ cộng số dư cho tối đa 10 account payout
+const GAS_FOR_ROYALTIES: Gas = 30_000_000_000_000;
 const GAS_FOR_NFT_TRANSFER: Gas = 15_000_000_000_000;
+const GAS_FOR_NFT_TOKEN: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_VALIDATE_SALE: Gas = 10_000_000_000_000;
+// Gas offer_batch tự dùng cho mỗi purchase (xoá sale, tạo cross contract call) và cho phần còn lại của call
+const GAS_FOR_PROCESS_PURCHASE: Gas = 10_000_000_000_000;
+const GAS_FOR_OFFER_BATCH: Gas = 10_000_000_000_000;
 
 //constant used to attach 0 NEAR to a call
 const NO_DEPOSIT: Balance = 0;
@@ -15,6 +23,66 @@ pub struct Payout {
     pub payout: HashMap<AccountId, U128>,
 } 
 
+// Các field cần dùng trong kết quả nft_token của NFT contract
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonToken {
+    pub owner_id: AccountId,
+    pub approved_account_ids: HashMap<AccountId, u64>
+}
+
+// Thông tin giao dịch truyền cho callback sau nft_transfer_payout
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Purchase {
+    pub nft_contract_id: AccountId,
+    pub token_id: TokenId,
+    pub seller_id: AccountId,
+    pub buyer_id: AccountId,
+    pub currency: CurrencyId,
+    pub price: U128,
+    pub market_fee: U128
+}
+
+impl Purchase {
+    // Kiểm tra payout trả về từ nft_transfer_payout, None nếu transfer lỗi hoặc payout không hợp lệ
+    pub(crate) fn valid_payout(&self) -> Option<HashMap<AccountId, U128>> {
+        promise_result_as_success().and_then(| value | {
+            let payout_object: Payout = near_sdk::serde_json::from_slice::<Payout>(&value).expect("Invalid payout object");
+
+            if payout_object.payout.len() > 10 || payout_object.payout.is_empty() {
+                env::log("Cannot have more than 10 royalities".as_bytes());
+                None
+            } else {
+                let mut remainder = self.price.0 - self.market_fee.0;
+
+                for &value in payout_object.payout.values() {
+                    remainder = remainder.checked_sub(value.0)?;
+                }
+
+                if remainder == 0 || remainder == 1 {
+                    Some(payout_object.payout)
+                } else {
+                    None
+                }
+            }
+        })
+    }
+
+    pub(crate) fn emit_purchased(self, payout: HashMap<AccountId, U128>) {
+        emit_event(EventLogVariant::SalePurchased(vec![ SalePurchasedLog {
+            nft_contract_id: self.nft_contract_id,
+            token_id: self.token_id,
+            seller_id: self.seller_id,
+            buyer_id: self.buyer_id,
+            currency: self.currency,
+            price: self.price,
+            market_fee: self.market_fee,
+            payout
+        } ]));
+    }
+}
+
 #[ext_contract(ext_nft_contract)]
 pub trait NFTContract {
     fn nft_transfer_payout(
@@ -26,15 +94,58 @@ pub trait NFTContract {
         balance: U128,
         max_len_payout: u32,
     ) -> Payout;
+
+    fn nft_token(&self, token_id: TokenId) -> Option<JsonToken>;
+
+    fn nft_redeem_voucher(&mut self, voucher: MintVoucher, signature: Base64VecU8, metadata: Value, receiver_id: Option<AccountId>) -> U128;
 }
 
 #[ext_contract(ext_self)]
 pub trait MarketContract {
-    fn resolve_purchase(&mut self, buyer_id: AccountId, price: U128) -> Promise;
-    fn ft_resolve_purchase(&mut self, buyer_id: AccountId, price: SalePrice) -> Promise;
+    fn resolve_purchase(&mut self, purchase: Purchase) -> Promise;
+    fn ft_resolve_purchase(&mut self, purchase: Purchase, amount: U128) -> Promise;
+    fn ft_resolve_offer_purchase(&mut self, purchase: Purchase);
+    fn resolve_withdraw_market_fee(&mut self, currency: AccountId, amount: U128);
+    fn resolve_buy_voucher(&mut self, purchase: Purchase, deposit: U128) -> bool;
+    fn resolve_ft_withdraw(&mut self, account_id: AccountId, ft_contract_id: AccountId, amount: U128);
+    fn resolve_validate_sale(&mut self, nft_contract_id: AccountId, token_id: TokenId, approval_id: u64) -> bool;
 }
 
 
+impl Sale {
+    pub(crate) fn to_log(&self) -> SaleLog {
+        SaleLog {
+            owner_id: self.owner_id.clone(),
+            nft_contract_id: self.nft_contract_id.clone(),
+            token_id: self.token_id.clone(),
+            sale_conditions: self.sale_conditions.clone(),
+            is_auction: self.auction.is_some(),
+            expires_at: self.expires_at
+        }
+    }
+
+    // Giá hiện tại theo currency, với dutch auction giá giảm dần theo thời gian
+    pub fn current_price(&self, currency: &CurrencyId) -> Option<U128> {
+        let price = self.sale_conditions.get(currency)?;
+
+        if let Some(dutch_auction) = &self.dutch_auction {
+            Some(U128(dutch_auction.price_at(currency, price.0, env::block_timestamp())))
+        } else {
+            Some(*price)
+        }
+    }
+
+    pub fn current_prices(&self) -> SaleConditions {
+        self.sale_conditions.keys()
+        .map(| currency | (currency.clone(), self.current_price(currency).unwrap()))
+        .collect()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map(| expires_at | env::block_timestamp() >= expires_at.0).unwrap_or(false)
+    }
+}
+
 #[near_bindgen]
 impl Contract {
 
@@ -46,23 +157,146 @@ impl Contract {
         let sale = self.internal_remove_sale(nft_contract_id, token_id);
         
         assert_eq!(env::predecessor_account_id(), sale.owner_id, "Must be owner id");
+
+        if let Some(auction) = sale.auction {
+            assert!(auction.highest_bid.is_none(), "Can not remove an auction with active bids");
+        }
     }
 
+    // Xoá nhiều sale của cùng 1 nft contract
     #[payable]
-    pub fn update_price(&mut self, nft_contract_id: AccountId, token_id: TokenId, price: SalePrice) {
+    pub fn remove_sales(&mut self, nft_contract_id: AccountId, token_ids: Vec<TokenId>) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+
+        for token_id in token_ids {
+            let sale = self.internal_remove_sale(nft_contract_id.clone(), token_id);
+            assert_eq!(owner_id, sale.owner_id, "Must be owner id");
+
+            if let Some(auction) = sale.auction {
+                assert!(auction.highest_bid.is_none(), "Can not remove an auction with active bids");
+            }
+        }
+    }
+
+    // Ai cũng có thể xoá sale đã hết hạn và nhận bounty từ storage deposit của seller
+    pub fn remove_expired_sales(&mut self, sales: Vec<(AccountId, TokenId)>) -> U128 {
+        let mut bounty = 0;
+
+        for (nft_contract_id, token_id) in sales {
+            let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+            let is_expired = self.sales.get(&contract_and_token_id).map(| sale | sale.is_expired()).unwrap_or(false);
+            if !is_expired {
+                log!("Skip {}: sale not expired", contract_and_token_id);
+                continue;
+            }
+
+            let sale = self.internal_remove_sale(nft_contract_id, token_id);
+            bounty += self.internal_take_expired_sale_bounty(&sale.owner_id);
+        }
+
+        if bounty > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(bounty);
+        }
+
+        U128(bounty)
+    }
+
+    // Kiểm tra owner và approval trên NFT contract, xoá sale nếu không còn hợp lệ
+    pub fn validate_sale(&mut self, nft_contract_id: AccountId, token_id: TokenId) -> Promise {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let sale = self.sales.get(&contract_and_token_id).expect("Not found sale");
+
+        ext_nft_contract::nft_token(
+            token_id.clone(),
+            &nft_contract_id,
+            NO_DEPOSIT,
+            GAS_FOR_NFT_TOKEN
+        ).then(ext_self::resolve_validate_sale(
+            nft_contract_id,
+            token_id,
+            sale.approval_id,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_VALIDATE_SALE
+        ))
+    }
+
+    #[private]
+    pub fn resolve_validate_sale(&mut self, nft_contract_id: AccountId, token_id: TokenId, approval_id: u64) -> bool {
+        // NFT contract lỗi thì không kết luận được, giữ nguyên sale
+        let value = if let Some(value) = promise_result_as_success() {
+            value
+        } else {
+            return true;
+        };
+
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let sale = if let Some(sale) = self.sales.get(&contract_and_token_id) {
+            sale
+        } else {
+            return false;
+        };
+
+        // Sale đã được đăng lại với approval mới trong lúc chờ kết quả
+        if sale.approval_id != approval_id {
+            return true;
+        }
+
+        let token = near_sdk::serde_json::from_slice::<Option<JsonToken>>(&value).ok().flatten();
+        let is_valid = token.map(| token | {
+            token.owner_id == sale.owner_id
+                && token.approved_account_ids.get(&env::current_account_id()) == Some(&sale.approval_id)
+        }).unwrap_or(false);
+
+        if !is_valid {
+            log!("Remove invalid sale {}", contract_and_token_id);
+            self.internal_delist(nft_contract_id, token_id);
+        }
+
+        is_valid
+    }
+
+    // Thêm/cập nhật giá theo currency, price = None để bỏ currency
+    // Với dutch auction, floor_price là giá sàn của currency, bắt buộc khi thêm currency mới
+    #[payable]
+    pub fn update_price(&mut self, nft_contract_id: AccountId, token_id: TokenId, currency: CurrencyId, price: Option<U128>, floor_price: Option<U128>) {
         assert_one_yocto();
 
         let contract_and_token_id = format!("{}{}{}", nft_contract_id.clone(), ".", token_id.clone());
 
         let mut sale = self.sales.get(&contract_and_token_id).expect("Not found sale");
         assert_eq!(env::predecessor_account_id(), sale.owner_id, "Must be sale owner");
-        sale.sale_conditions = price;
+        assert!(sale.auction.is_none(), "Can not update price of an auction");
+
+        if let Some(price) = price {
+            self.internal_assert_currency_allowed(&currency);
+            sale.sale_conditions.insert(currency.clone(), price);
+        } else {
+            sale.sale_conditions.remove(&currency);
+            assert!(!sale.sale_conditions.is_empty(), "Sale conditions must have at least one currency");
+        }
+
+        if let Some(dutch_auction) = sale.dutch_auction.as_mut() {
+            if price.is_none() {
+                assert!(floor_price.is_none(), "Can not set floor_price for a removed currency");
+                dutch_auction.floor_price.remove(&currency);
+            } else if let Some(floor_price) = floor_price {
+                dutch_auction.floor_price.insert(currency.clone(), floor_price);
+            }
+            dutch_auction.assert_valid(&sale.sale_conditions);
+        } else {
+            assert!(floor_price.is_none(), "floor_price is only for dutch auction sales");
+        }
 
         self.sales.insert(&contract_and_token_id, &sale);
+
+        emit_event(EventLogVariant::SaleUpdated(vec![ sale.to_log() ]));
     }
 
+    // Chỉ thu đúng giá hiện tại và hoàn lại phần thừa, max_price bảo vệ buyer khi seller update_price trước đó
     #[payable]
-    pub fn offer(&mut self, nft_contract_id: AccountId, token_id: TokenId) {
+    pub fn offer(&mut self, nft_contract_id: AccountId, token_id: TokenId, max_price: Option<U128>) {
         let deposit = env::attached_deposit();
         assert!(deposit > 0, "Attached deposit must be greater than 0");
 
@@ -72,77 +306,138 @@ impl Contract {
 
         let buyer_id = env::predecessor_account_id();
         assert_ne!(buyer_id, sale.owner_id, "Can not bid on your own sale");
+        assert!(sale.auction.is_none(), "Use place_bid for auction sales");
+        assert!(!sale.is_expired(), "Sale has expired");
 
-        let price = sale.sale_conditions.amount.0;
+        // Check sale conditions
+        let price = sale.current_price(&NEAR_CURRENCY.to_string()).expect("Sale does not accept NEAR").0;
         assert!(deposit >= price, "Attached deposit must be greater than or equal current price: {}", price);
+        if let Some(max_price) = max_price {
+            assert!(price <= max_price.0, "Current price {} is greater than max_price", price);
+        }
 
-        // Check sale conditions
-        assert!(sale.sale_conditions.is_native, "Only accept payout with NEAR");
+        let refund = deposit - price;
+        if refund > 0 {
+            Promise::new(buyer_id.clone()).transfer(refund);
+        }
 
         self.process_purchase(
             nft_contract_id,
             token_id,
-            U128(deposit),
+            U128(price),
             buyer_id
         );
     }
 
+    // Mua nhiều token trong 1 transaction, sale không hợp lệ, vượt max_price hoặc không đủ tiền sẽ bị bỏ qua
+    // Deposit chưa dùng được hoàn lại, purchase lỗi được hoàn lại trong resolve_purchase
+    #[payable]
+    pub fn offer_batch(&mut self, tokens: Vec<(AccountId, TokenId, Option<U128>)>) {
+        let deposit = env::attached_deposit();
+        assert!(deposit > 0, "Attached deposit must be greater than 0");
+        assert!(!tokens.is_empty(), "Tokens must not be empty");
+
+        // Mỗi purchase cần gas cho process_purchase, nft_transfer_payout và resolve_purchase
+        let gas_required = GAS_FOR_OFFER_BATCH + (GAS_FOR_PROCESS_PURCHASE + GAS_FOR_NFT_TRANSFER + GAS_FOR_ROYALTIES) * tokens.len() as u64;
+        assert!(env::prepaid_gas() >= gas_required, "Attach at least {} gas for {} purchases", gas_required, tokens.len());
+
+        let buyer_id = env::predecessor_account_id();
+        let mut remaining = deposit;
+
+        for (nft_contract_id, token_id, max_price) in tokens {
+            let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+
+            let price = match self.sales.get(&contract_and_token_id) {
+                Some(sale) if sale.owner_id != buyer_id && sale.auction.is_none() && !sale.is_expired() => sale.current_price(&NEAR_CURRENCY.to_string()),
+                _ => None
+            };
+            let price = match price {
+                Some(price) => price.0,
+                None => {
+                    log!("Skip {}: sale not available", contract_and_token_id);
+                    continue;
+                }
+            };
+
+            if let Some(max_price) = max_price {
+                if price > max_price.0 {
+                    log!("Skip {}: current price {} is greater than max_price", contract_and_token_id, price);
+                    continue;
+                }
+            }
+
+            if price > remaining {
+                log!("Skip {}: not enough deposit for price {}", contract_and_token_id, price);
+                continue;
+            }
+            remaining -= price;
+
+            self.process_purchase(
+                nft_contract_id,
+                token_id,
+                U128(price),
+                buyer_id.clone()
+            );
+        }
+
+        if remaining > 0 {
+            Promise::new(buyer_id).transfer(remaining);
+        }
+    }
+
     #[private]
     pub fn process_purchase(&mut self, nft_contract_id: AccountId, token_id: TokenId, price: U128, buyer_id: AccountId) -> Promise {
         let sale = self.internal_remove_sale(nft_contract_id.clone(), token_id.clone());
 
+        // Phí market được trừ trước khi chia cho seller và royalties
+        let market_fee = self.internal_market_fee(&nft_contract_id, &sale.owner_id, price.0);
+
         // Cross contract call
         ext_nft_contract::nft_transfer_payout(
             buyer_id.clone(), 
-            token_id, 
+            token_id.clone(), 
             sale.approval_id, 
             "Payout from market contract".to_string(), 
-            price, 
+            U128(price.0 - market_fee), 
             10, 
             &nft_contract_id, 
             1, 
             GAS_FOR_NFT_TRANSFER
         ).then(ext_self::resolve_purchase(
-            buyer_id, 
-            price, 
+            Purchase {
+                nft_contract_id,
+                token_id,
+                seller_id: sale.owner_id,
+                buyer_id,
+                currency: NEAR_CURRENCY.to_string(),
+                price,
+                market_fee: U128(market_fee)
+            },
             &env::current_account_id(), 
             NO_DEPOSIT, 
             GAS_FOR_ROYALTIES
         ))
     }
 
-    pub fn resolve_purchase(&mut self, buyer_id: AccountId, price: U128) -> U128 {
-        let payout_option = promise_result_as_success().and_then(| value | {
-            let payout_object: Payout = near_sdk::serde_json::from_slice::<Payout>(&value).expect("Invalid payout object");
-
-            if payout_object.payout.len() > 10 || payout_object.payout.is_empty() {
-                env::log("Cannot have more than 10 royalities".as_bytes());
-                None
-            } else {
-                let mut remainder = price.0;
-
-                for &value in payout_object.payout.values() {
-                    remainder = remainder.checked_sub(value.0)?;
-                }
-
-                if remainder == 0 || remainder == 1 {
-                    Some(payout_object.payout)
-                } else {
-                    None
-                }
-            }
-        });
+    #[private]
+    pub fn resolve_purchase(&mut self, purchase: Purchase) -> U128 {
+        let price = purchase.price;
 
-        let payout = if let Some(payout_option) = payout_option {
-            payout_option
+        let payout = if let Some(payout) = purchase.valid_payout() {
+            payout
         } else {
-            Promise::new(buyer_id).transfer(u128::from(price));
+            // Sale đã bị xoá trong process_purchase nên listing không hợp lệ (token đã chuyển, approval bị revoke) cũng được dọn luôn
+            self.internal_credit(&purchase.buyer_id, &NEAR_CURRENCY.to_string(), price.0);
             return price;
         };
 
-        for (reciver_id, amount) in payout {
-            Promise::new(reciver_id).transfer(u128::from(amount));
+        // Cộng vào số dư, các account tự rút qua withdraw
+        for (reciver_id, amount) in payout.iter() {
+            self.internal_credit(reciver_id, &NEAR_CURRENCY.to_string(), amount.0);
         }
+        self.internal_add_market_fee(NEAR_CURRENCY.to_string(), purchase.market_fee.0);
+        purchase.emit_purchased(payout);
+
         price
     }
 }
\ No newline at end of file
@@ -31,6 +31,7 @@ impl Contract {
         let start = u128::from(from_index.unwrap_or(U128(0)));
 
          self.sales.values()
+         .filter(| sale | !sale.is_expired())
          .skip(start as usize)
          .take(limit.unwrap_or(0) as usize)
          .collect()
@@ -47,9 +48,10 @@ impl Contract {
 
         contract_token_ids.as_vector()
         .iter()
+        .map( | contract_token_id | self.sales.get(&contract_token_id).unwrap() )
+        .filter( | sale | !sale.is_expired() )
         .skip(start as usize)
         .take(limit.unwrap_or(0) as usize)
-        .map( | contract_token_id | self.sales.get(&contract_token_id).unwrap() )
         .collect()
     }
 
@@ -64,10 +66,18 @@ impl Contract {
 
         let start = u128::from(from_index.unwrap_or(U128(0)));
         token_ids.iter()
+        .map(|token_id| self.sales.get(&format!("{}{}{}", contract_id, ".", token_id)).unwrap())
+        .filter(|sale| !sale.is_expired())
         .skip(start as usize)
         .take(limit.unwrap_or(0) as usize)
-        .map(|token_id| self.sales.get(&format!("{}{}{}", contract_id, ".", token_id)).unwrap())
         .collect()
     }
 
+    // Lấy giá hiện tại của sale theo từng currency (dutch auction giảm giá theo thời gian)
+    pub fn get_current_price(&self, nft_contract_id: AccountId, token_id: TokenId) -> SaleConditions {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let sale = self.sales.get(&contract_and_token_id).expect("Not found sale");
+
+        sale.current_prices()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,221 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde_json::Value;
+use near_sdk::is_promise_success;
+
+//GAS constants to attach to calls
+const GAS_FOR_REDEEM_VOUCHER: Gas = 40_000_000_000_000;
+const GAS_FOR_RESOLVE_BUY_VOUCHER: Gas = 15_000_000_000_000;
+//constant used to attach 0 NEAR to a call
+const NO_DEPOSIT: Balance = 0;
+
+// Voucher do creator ký off-chain, token chỉ được mint khi có người mua (lazy minting)
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintVoucher {
+    pub token_id: TokenId,
+    pub creator_id: AccountId,
+    pub metadata_hash: Base64VecU8,
+    pub price: U128, // NEAR
+    pub royalty: HashMap<AccountId, u32>,
+    pub expires_at: Option<U64>, // nanoseconds
+    pub market_id: Option<AccountId> // phải là market này để market thu price và trả cho creator
+}
+
+// Voucher được creator đăng bán trên market, token chưa tồn tại trên NFT contract
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VoucherListing {
+    pub nft_contract_id: NFTContractId,
+    pub voucher: MintVoucher,
+    pub signature: Base64VecU8
+}
+
+impl VoucherListing {
+    pub fn is_expired(&self) -> bool {
+        self.voucher.expires_at.map(| expires_at | env::block_timestamp() >= expires_at.0).unwrap_or(false)
+    }
+
+    pub(crate) fn to_log(&self) -> SaleLog {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(NEAR_CURRENCY.to_string(), self.voucher.price);
+
+        SaleLog {
+            owner_id: self.voucher.creator_id.clone(),
+            nft_contract_id: self.nft_contract_id.clone(),
+            token_id: self.voucher.token_id.clone(),
+            sale_conditions,
+            is_auction: false,
+            expires_at: self.voucher.expires_at
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * - Creator đăng bán voucher, storage của listing được cover bởi storage deposit như sale
+     * - Voucher phải chỉ định market này làm market_id, NFT contract kiểm tra chữ ký khi mint
+     */
+    pub fn list_voucher(&mut self, nft_contract_id: AccountId, voucher: MintVoucher, signature: Base64VecU8) {
+        let creator_id = env::predecessor_account_id();
+        assert_eq!(creator_id, voucher.creator_id, "Only voucher creator can list it");
+        assert_eq!(voucher.market_id, Some(env::current_account_id()), "Voucher must be redeemable through this market");
+
+        let listing = VoucherListing {
+            nft_contract_id,
+            voucher,
+            signature
+        };
+        assert!(!listing.is_expired(), "Voucher has expired");
+
+        let contract_and_token_id = format!("{}{}{}", listing.nft_contract_id, ".", listing.voucher.token_id);
+        if self.vouchers.get(&contract_and_token_id).is_some() {
+            self.internal_remove_voucher(listing.nft_contract_id.clone(), listing.voucher.token_id.clone());
+        }
+
+        // Check cover storage
+        self.internal_assert_storage_available(&creator_id, 1);
+
+        self.internal_add_voucher(listing);
+    }
+
+    #[payable]
+    pub fn remove_voucher(&mut self, nft_contract_id: AccountId, token_id: TokenId) {
+        assert_one_yocto();
+
+        let listing = self.internal_remove_voucher(nft_contract_id, token_id);
+        assert_eq!(env::predecessor_account_id(), listing.voucher.creator_id, "Must be voucher creator");
+    }
+
+    pub fn get_voucher(&self, nft_contract_id: AccountId, token_id: TokenId) -> Option<VoucherListing> {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        self.vouchers.get(&contract_and_token_id)
+    }
+
+    pub fn get_vouchers_by_creator(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<VoucherListing> {
+        let keys = if let Some(keys) = self.vouchers_by_creator.get(&account_id) {
+            keys
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        keys.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .map(| contract_and_token_id | self.vouchers.get(&contract_and_token_id).unwrap())
+        .collect()
+    }
+
+    /**
+     * - Deposit phải cover giá và phí storage của token trên NFT contract
+     * - Market giữ price, phần còn lại chuyển cho NFT contract để trả storage, phần thừa được NFT contract refund thẳng cho buyer
+     * - Phí market được trừ vào price như sale thường
+     */
+    #[payable]
+    pub fn buy_voucher(&mut self, nft_contract_id: AccountId, token_id: TokenId, metadata: Value) -> Promise {
+        let deposit = env::attached_deposit();
+        let buyer_id = env::predecessor_account_id();
+
+        let listing = self.internal_remove_voucher(nft_contract_id.clone(), token_id.clone());
+        assert_ne!(buyer_id, listing.voucher.creator_id, "Can not buy your own voucher");
+        assert!(!listing.is_expired(), "Voucher has expired");
+
+        let price = listing.voucher.price;
+        assert!(deposit > price.0, "Attached deposit must cover price and storage");
+
+        let market_fee = self.internal_market_fee(&nft_contract_id, &listing.voucher.creator_id, price.0);
+
+        let purchase = Purchase {
+            nft_contract_id: nft_contract_id.clone(),
+            token_id,
+            seller_id: listing.voucher.creator_id.clone(),
+            buyer_id: buyer_id.clone(),
+            currency: NEAR_CURRENCY.to_string(),
+            price,
+            market_fee: U128(market_fee)
+        };
+
+        ext_nft_contract::nft_redeem_voucher(
+            listing.voucher.clone(),
+            listing.signature.clone(),
+            metadata,
+            Some(buyer_id),
+            &nft_contract_id,
+            deposit - price.0,
+            GAS_FOR_REDEEM_VOUCHER
+        ).then(ext_self::resolve_buy_voucher(
+            purchase,
+            U128(deposit),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_BUY_VOUCHER
+        ))
+    }
+
+    // Chỉ dùng price market đang giữ, không tin giá trị trả về từ NFT contract
+    #[private]
+    pub fn resolve_buy_voucher(&mut self, purchase: Purchase, deposit: U128) -> bool {
+        let price = purchase.price.0;
+
+        if !is_promise_success() {
+            // Mint lỗi thì phần deposit gửi cho NFT contract được trả lại market, hoàn lại toàn bộ cho buyer
+            // Voucher không được đăng lại, creator tự đăng lại nếu voucher vẫn hợp lệ
+            self.internal_credit(&purchase.buyer_id, &NEAR_CURRENCY.to_string(), deposit.0);
+            return false;
+        }
+
+        let proceeds = price - purchase.market_fee.0;
+        self.internal_credit(&purchase.seller_id, &NEAR_CURRENCY.to_string(), proceeds);
+        self.internal_add_market_fee(NEAR_CURRENCY.to_string(), purchase.market_fee.0);
+
+        let mut payout = HashMap::new();
+        payout.insert(purchase.seller_id.clone(), U128(proceeds));
+        purchase.emit_purchased(payout);
+
+        true
+    }
+
+    pub(crate) fn internal_add_voucher(&mut self, listing: VoucherListing) {
+        let contract_and_token_id = format!("{}{}{}", listing.nft_contract_id, ".", listing.voucher.token_id);
+        let creator_id = listing.voucher.creator_id.clone();
+
+        let mut by_creator = self.vouchers_by_creator.get(&creator_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::InnerVouchersByCreatorKey {
+                    account_id_hash: hash_account_id(&creator_id)
+                }.try_to_vec().unwrap()
+            )
+        });
+        by_creator.insert(&contract_and_token_id);
+        self.vouchers_by_creator.insert(&creator_id, &by_creator);
+
+        emit_event(EventLogVariant::SaleCreated(vec![ listing.to_log() ]));
+
+        self.vouchers.insert(&contract_and_token_id, &listing);
+    }
+
+    pub(crate) fn internal_remove_voucher(&mut self, nft_contract_id: AccountId, token_id: TokenId) -> VoucherListing {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, ".", token_id);
+        let listing = self.vouchers.remove(&contract_and_token_id).expect("Not found voucher");
+        let creator_id = listing.voucher.creator_id.clone();
+
+        let mut by_creator = self.vouchers_by_creator.get(&creator_id).expect("Not found voucher by creator");
+        by_creator.remove(&contract_and_token_id);
+        if by_creator.is_empty() {
+            self.vouchers_by_creator.remove(&creator_id);
+        } else {
+            self.vouchers_by_creator.insert(&creator_id, &by_creator);
+        }
+
+        emit_event(EventLogVariant::SaleRemoved(vec![ SaleRemovedLog {
+            owner_id: creator_id,
+            nft_contract_id,
+            token_id
+        } ]));
+
+        listing
+    }
+}
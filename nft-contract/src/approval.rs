@@ -1,6 +1,7 @@
 use crate::*;
 
 const GAS_FOR_NFT_APPROVE: Gas = 10_000_000_000_000;
+const GAS_FOR_NFT_ON_REVOKE: Gas = 10_000_000_000_000;
 const NO_DEPOSIT: Balance = 0;
 
 pub trait NonFungibleTokenApproval {
@@ -19,6 +20,31 @@ pub trait NonFungibleTokenApprovalReceiver {
         approval_id: u64,
         msg: String
     );
+
+    fn nft_on_batch_approve(
+        &mut self,
+        tokens: Vec<(TokenId, u64)>,
+        owner_id: AccountId,
+        msg: String
+    );
+
+    // Thông báo cho account bị revoke (vd. market) để xoá listing, account không implement thì bỏ qua
+    fn nft_on_revoke(
+        &mut self,
+        token_id: TokenId,
+        approval_id: u64
+    );
+}
+
+// Fire-and-forget, revoke vẫn thành công dù receiver lỗi
+pub(crate) fn notify_revoke(token_id: TokenId, account_id: AccountId, approval_id: u64) {
+    ext_non_fungible_token_approval_receiver::nft_on_revoke(
+        token_id,
+        approval_id,
+        &account_id,
+        NO_DEPOSIT,
+        GAS_FOR_NFT_ON_REVOKE
+    );
 }
 
 #[near_bindgen]
@@ -28,28 +54,15 @@ impl NonFungibleTokenApproval for Contract {
     fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>) {
         assert_at_least_one_yocto();
 
-        let mut token = self.tokens_by_id.get(&token_id).expect("Not found token");
-
-        assert_eq!(&env::predecessor_account_id(), &token.owner_id, "Predecessor must be the token owner");
-
-        let approval_id = token.next_approval_id;
-        let is_new_approval = token.approved_account_ids.insert(account_id.clone(), approval_id).is_none();
-
-        let storage_used = if is_new_approval {
-            bytes_for_approved_account_id(&account_id)
-        } else {
-            0
-        };
-
-        token.next_approval_id += 1;
-        self.tokens_by_id.insert(&token_id, &token);
+        let owner_id = env::predecessor_account_id();
+        let (approval_id, storage_used) = self.internal_approve(&token_id, &owner_id, &account_id);
 
         refund_deposit(storage_used);
 
         if let Some(msg) = msg {
             ext_non_fungible_token_approval_receiver::nft_on_approve(
                 token_id, 
-                token.owner_id, 
+                owner_id, 
                 approval_id, 
                 msg, 
                 &account_id, 
@@ -85,10 +98,12 @@ impl NonFungibleTokenApproval for Contract {
         let sender_id = env::predecessor_account_id();
         assert_eq!(&sender_id, &token.owner_id);
 
-        if token.approved_account_ids.remove(&account_id).is_some() {
+        if let Some(approval_id) = token.approved_account_ids.remove(&account_id) {
             // refund 
-            refund_approved_account_ids_iter(sender_id, [account_id].iter());
+            refund_approved_account_ids_iter(sender_id, [account_id.clone()].iter());
             self.tokens_by_id.insert(&token_id, &token);
+
+            notify_revoke(token_id, account_id, approval_id);
         }
     }
 
@@ -102,9 +117,45 @@ impl NonFungibleTokenApproval for Contract {
 
         if !token.approved_account_ids.is_empty() {
             refund_approved_account_ids(sender_id, &token.approved_account_ids);
-            token.approved_account_ids.clear();
+
+            for (account_id, approval_id) in token.approved_account_ids.drain() {
+                notify_revoke(token_id.clone(), account_id, approval_id);
+            }
 
             self.tokens_by_id.insert(&token_id, &token);
         }
     }
-}
\ No newline at end of file
+}
+
+#[near_bindgen]
+impl Contract {
+    // Approve nhiều token cho 1 account (vd. market) trong 1 transaction
+    #[payable]
+    pub fn nft_approve_batch(&mut self, token_ids: Vec<TokenId>, account_id: AccountId, msg: Option<String>) {
+        assert_at_least_one_yocto();
+        assert!(!token_ids.is_empty(), "Token ids must not be empty");
+
+        let owner_id = env::predecessor_account_id();
+        let mut storage_used = 0;
+        let mut tokens = Vec::with_capacity(token_ids.len());
+
+        for token_id in token_ids {
+            let (approval_id, token_storage_used) = self.internal_approve(&token_id, &owner_id, &account_id);
+            storage_used += token_storage_used;
+            tokens.push((token_id, approval_id));
+        }
+
+        refund_deposit(storage_used);
+
+        if let Some(msg) = msg {
+            ext_non_fungible_token_approval_receiver::nft_on_batch_approve(
+                tokens,
+                owner_id,
+                msg,
+                &account_id,
+                NO_DEPOSIT,
+                env::prepaid_gas() - GAS_FOR_NFT_APPROVE,
+            ).as_return();
+        }
+    }
+}
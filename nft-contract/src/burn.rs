@@ -0,0 +1,65 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * - Owner hoặc account được approve mới được burn token
+     * - Xoá token khỏi tokens_by_id, token_metadata_by_id và ds sở hữu của owner
+     * - Báo cho các account đã được approve (market) để xoá sale
+     * - Refund lại phí storage được giải phóng cho owner
+     */
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId, approval_id: Option<u64>, memo: Option<String>) {
+        assert_one_yocto();
+        let before_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+
+        let token = self.tokens_by_id.get(&token_id).expect("Not found token");
+
+        // check owner
+        let mut authorized_id = None;
+        if sender_id != token.owner_id {
+            let actual_approval_id = token.approved_account_ids.get(&sender_id).expect("Sender must be the token owner or approved account");
+
+            if let Some(enforced_approval_id) = approval_id {
+                assert_eq!(
+                    actual_approval_id, &enforced_approval_id,
+                    "The actual approval id {} is different from the given approval id {}",
+                    actual_approval_id, enforced_approval_id
+                );
+            }
+            authorized_id = Some(sender_id.clone());
+        }
+
+        self.tokens_by_id.remove(&token_id);
+        self.token_metadata_by_id.remove(&token_id);
+        self.creator_by_token_id.remove(&token_id);
+        self.frozen_token_metadata.remove(&token_id);
+        self.internal_remove_token_from_owner(&token_id, &token.owner_id);
+
+        for (account_id, approval_id) in token.approved_account_ids.iter() {
+            if account_id != &sender_id {
+                notify_revoke(token_id.clone(), account_id.clone(), *approval_id);
+            }
+        }
+
+        // NFT BURN LOG
+        let nft_burn_log: EventLog = EventLog {
+            standard: "nep171".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::NftBurn(vec![ NftBurnLog {
+                owner_id: token.owner_id.to_string(),
+                authorized_id,
+                token_ids: vec![token_id.to_string()],
+                memo
+            } ])
+        };
+        env::log(nft_burn_log.to_string().as_bytes());
+
+        // Storage của token, metadata và các approval được trả lại cho owner
+        let storage_released = before_storage_usage.saturating_sub(env::storage_usage());
+        if storage_released > 0 {
+            Promise::new(token.owner_id).transfer(Balance::from(storage_released) * env::storage_byte_cost());
+        }
+    }
+}
@@ -4,7 +4,7 @@ use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::serde_json;
 
 /// Enum that represents the data type of the EventLog.
-/// The enum can either be an NftMint or an NftTransfer.
+/// The enum can either be an NftMint, an NftTransfer, an NftBurn or an NftMetadataUpdate.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "event", content = "data")]
 #[serde(rename_all = "snake_case")]
@@ -13,6 +13,8 @@ use near_sdk::serde_json;
 pub enum EventLogVariant {
     NftMint(Vec<NftMintLog>),
     NftTransfer(Vec<NftTransferLog>),
+    NftBurn(Vec<NftBurnLog>),
+    NftMetadataUpdate(Vec<NftMetadataUpdateLog>),
 }
 
 /// Interface to capture data about an event
@@ -79,6 +81,41 @@ pub struct NftTransferLog {
     pub memo: Option<String>,
 }
 
+/// An event log to capture token burning
+///
+/// Arguments
+/// * `owner_id`: owner of the token before burning
+/// * `authorized_id`: approved account to burn
+/// * `token_ids`: ["1", "abc"]
+/// * `memo`: optional message
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+    pub owner_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<String>,
+
+    pub token_ids: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// An event log to capture token metadata being updated (NEP-171 1.1.0)
+///
+/// Arguments
+/// * `token_ids`: ["1", "abc"]
+/// * `memo`: optional message
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftMetadataUpdateLog {
+    pub token_ids: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,4 +173,34 @@ mod tests {
         };
         assert_eq!(expected, log.to_string());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn nep_format_burn() {
+        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_burn","data":[{"owner_id":"user1.near","token_ids":["token"]}]}"#;
+        let log = EventLog {
+            standard: "nep171".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::NftBurn(vec![NftBurnLog {
+                owner_id: "user1.near".to_string(),
+                authorized_id: None,
+                token_ids: vec!["token".to_string()],
+                memo: None,
+            }]),
+        };
+        assert_eq!(expected, log.to_string());
+    }
+
+    #[test]
+    fn nep_format_metadata_update() {
+        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_metadata_update","data":[{"token_ids":["token"]}]}"#;
+        let log = EventLog {
+            standard: "nep171".to_string(),
+            version: "1.1.0".to_string(),
+            event: EventLogVariant::NftMetadataUpdate(vec![NftMetadataUpdateLog {
+                token_ids: vec!["token".to_string()],
+                memo: None,
+            }]),
+        };
+        assert_eq!(expected, log.to_string());
+    }
+}
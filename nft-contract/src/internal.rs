@@ -4,6 +4,10 @@ use crate::*;
 
 #[near_bindgen]
 impl Contract {
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only contract owner can call this method");
+    }
+
     pub(crate) fn internal_add_token_to_owner(&mut self, token_id: &TokenId, account_id: &AccountId) {
 
         // Nếu account_id đã có ds token rồi, thì sẽ lấy ds token đang có
@@ -19,6 +23,39 @@ impl Contract {
         self.tokens_per_owner.insert(account_id, &tokens_set);
     }
 
+    // Lưu token, metadata, creator và ds sở hữu của owner, ghi log nft_mint
+    pub(crate) fn internal_mint(&mut self, token_id: &TokenId, metadata: TokenMetadata, receiver_id: &AccountId, creator_id: &AccountId, royalty: HashMap<AccountId, u32>) {
+        let token = Token {
+            owner_id: receiver_id.clone(),
+            approved_account_ids: HashMap::default(),
+            next_approval_id: 0,
+            royalty
+        };
+
+        assert!(
+            self.tokens_by_id.insert(token_id, &token).is_none(),
+            "Token already exsits"
+        );
+
+        self.token_metadata_by_id.insert(token_id, &metadata);
+        self.creator_by_token_id.insert(token_id, creator_id);
+
+        // set token per owner
+        self.internal_add_token_to_owner(token_id, &token.owner_id);
+
+        // NFT MINT LOG
+        let nft_mint_log: EventLog = EventLog {
+            standard: "nep171".to_string(),
+            version: "1.0.0".to_string(),
+            event: EventLogVariant::NftMint(vec![ NftMintLog {
+                owner_id: token.owner_id.to_string(),
+                token_ids: vec![token_id.to_string()],
+                memo: None
+            } ])
+        };
+        env::log(nft_mint_log.to_string().as_bytes());
+    }
+
     pub(crate) fn internal_remove_token_from_owner(&mut self, token_id: &TokenId, account_id: &AccountId) {
         let mut tokens_set = self.tokens_per_owner.get(account_id).expect("Token should be owned by sender");
 
@@ -30,6 +67,27 @@ impl Contract {
         }
     }
 
+    // Thêm approval cho account_id, trả về approval_id và storage sử dụng
+    pub(crate) fn internal_approve(&mut self, token_id: &TokenId, owner_id: &AccountId, account_id: &AccountId) -> (u64, u64) {
+        let mut token = self.tokens_by_id.get(token_id).expect("Not found token");
+
+        assert_eq!(owner_id, &token.owner_id, "Predecessor must be the token owner");
+
+        let approval_id = token.next_approval_id;
+        let is_new_approval = token.approved_account_ids.insert(account_id.clone(), approval_id).is_none();
+
+        let storage_used = if is_new_approval {
+            bytes_for_approved_account_id(account_id)
+        } else {
+            0
+        };
+
+        token.next_approval_id += 1;
+        self.tokens_by_id.insert(token_id, &token);
+
+        (approval_id, storage_used)
+    }
+
     // return lại data token cũ trước khi thực hiện transfer
     /**
      * - Kiểm tra token_id có tồn tại không?
@@ -101,6 +159,8 @@ impl Contract {
 
             env::log(&nft_transfer_log.to_string().as_bytes());
 
+            self.internal_notify_transfer_listeners(sender_id, receiver_id, token_id, &token);
+
             token
     }
 }
\ No newline at end of file
@@ -1,9 +1,9 @@
 use near_sdk::collections::{LazyOption, UnorderedSet, UnorderedMap};
 use near_sdk::{near_bindgen, CryptoHash, Balance, env, Promise, ext_contract, log, Gas, PromiseResult, PromiseOrValue, PanicOnDefault};
-use near_sdk::{AccountId, collections::LookupMap};
+use near_sdk::{AccountId, collections::{LookupMap, LookupSet}};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
 use std::collections::HashMap;
 
 pub type TokenId = String;
@@ -16,6 +16,10 @@ pub use crate::nft_core::*;
 pub use crate::approval::*;
 pub use crate::event::*;
 pub use crate::royalty::*;
+pub use crate::listener::*;
+pub use crate::series::*;
+pub use crate::mint_sale::*;
+pub use crate::voucher::*;
 
 mod metadata;
 mod mint;
@@ -26,6 +30,14 @@ mod nft_core;
 mod approval;
 mod event;
 mod royalty;
+mod listener;
+mod burn;
+mod minter;
+mod series;
+mod mint_sale;
+mod voucher;
+mod metadata_update;
+mod migration;
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
@@ -38,7 +50,37 @@ struct Contract {
 
     pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>, // Mapping token id với token metadata
 
-    pub metadata: LazyOption<NFTContractMetadata>
+    pub metadata: LazyOption<NFTContractMetadata>,
+
+    pub transfer_listeners: UnorderedSet<AccountId>, // Danh sách market nhận thông báo khi token được transfer
+
+    pub minters: UnorderedSet<AccountId>, // Danh sách account được mint, ngoài owner
+
+    pub public_mint: bool, // Cho phép ai cũng có thể mint
+
+    pub series_by_id: UnorderedMap<SeriesId, Series>, // Series với metadata dùng chung cho các edition
+
+    pub next_series_id: SeriesId,
+
+    pub mint_sale: LazyOption<MintSale>, // Đợt bán edition của series (primary sale)
+
+    pub minted_by_account: LookupMap<(SeriesId, AccountId), u64>, // Số token đã mua qua mint sale theo series và account
+
+    pub storage_deposits: LookupMap<AccountId, Balance>, // NEAR nạp trước để trả storage khi mua bằng FT
+
+    pub pending_mint_proceeds: LookupMap<(AccountId, AccountId), Balance>, // Tiền bán bằng FT chuyển lỗi theo recipient và FT contract
+
+    pub allowlist: LookupMap<(SeriesId, AccountId), Option<u64>>, // Allowlist presale theo series và allocation riêng (None là mặc định)
+
+    pub presale_minted_by_account: LookupMap<(SeriesId, AccountId), u64>, // Số token đã mua trong presale theo series và account
+
+    pub creator_keys: LookupMap<AccountId, Vec<u8>>, // Public key ed25519 của creator dùng để ký voucher
+
+    pub redeemed_vouchers: LookupSet<TokenId>, // Token id đã được mint từ voucher, burn không xoá
+
+    pub creator_by_token_id: LookupMap<TokenId, AccountId>, // Creator của token, được quyền cập nhật metadata
+
+    pub frozen_token_metadata: LookupSet<TokenId> // Token có metadata không thể cập nhật nữa
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -49,7 +91,20 @@ pub enum StorageKey {
     TokenMetadataByIdKey,
     TokenPerOwnerInnerKey {
         account_id_hash: CryptoHash
-    }
+    },
+    TransferListenerKey,
+    MinterKey,
+    SeriesByIdKey,
+    MintSaleKey,
+    MintedByAccountKey,
+    AllowlistKey,
+    PresaleMintedByAccountKey,
+    CreatorKeyKey,
+    CreatorByTokenIdKey,
+    FrozenTokenMetadataKey,
+    StorageDepositKey,
+    PendingMintProceedsKey,
+    RedeemedVoucherKey
 }
 
 #[near_bindgen]
@@ -64,7 +119,22 @@ impl Contract {
             ),
             tokens_per_owner: LookupMap::new(StorageKey::TokenPerOwnerKey.try_to_vec().unwrap()),
             tokens_by_id: LookupMap::new(StorageKey::TokenByIdKey.try_to_vec().unwrap()),
-            token_metadata_by_id: UnorderedMap::new(StorageKey::TokenMetadataByIdKey.try_to_vec().unwrap())
+            token_metadata_by_id: UnorderedMap::new(StorageKey::TokenMetadataByIdKey.try_to_vec().unwrap()),
+            transfer_listeners: UnorderedSet::new(StorageKey::TransferListenerKey.try_to_vec().unwrap()),
+            minters: UnorderedSet::new(StorageKey::MinterKey.try_to_vec().unwrap()),
+            public_mint: false,
+            series_by_id: UnorderedMap::new(StorageKey::SeriesByIdKey.try_to_vec().unwrap()),
+            next_series_id: 0,
+            mint_sale: LazyOption::new(StorageKey::MintSaleKey.try_to_vec().unwrap(), None),
+            minted_by_account: LookupMap::new(StorageKey::MintedByAccountKey.try_to_vec().unwrap()),
+            storage_deposits: LookupMap::new(StorageKey::StorageDepositKey.try_to_vec().unwrap()),
+            pending_mint_proceeds: LookupMap::new(StorageKey::PendingMintProceedsKey.try_to_vec().unwrap()),
+            allowlist: LookupMap::new(StorageKey::AllowlistKey.try_to_vec().unwrap()),
+            presale_minted_by_account: LookupMap::new(StorageKey::PresaleMintedByAccountKey.try_to_vec().unwrap()),
+            creator_keys: LookupMap::new(StorageKey::CreatorKeyKey.try_to_vec().unwrap()),
+            redeemed_vouchers: LookupSet::new(StorageKey::RedeemedVoucherKey.try_to_vec().unwrap()),
+            creator_by_token_id: LookupMap::new(StorageKey::CreatorByTokenIdKey.try_to_vec().unwrap()),
+            frozen_token_metadata: LookupSet::new(StorageKey::FrozenTokenMetadataKey.try_to_vec().unwrap())
         }
     }
 
@@ -82,11 +152,18 @@ impl Contract {
             reference_hash: None
         })
     }
+
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        Self::internal_migrate()
+    }
 }
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use super::*;
+    use crate::migration::*;
 
     use near_sdk::test_utils::{VMContextBuilder, accounts};
     use near_sdk::{testing_env};
@@ -138,7 +215,7 @@ mod tests {
         );
 
         let token_id = "VBI_NFT".to_string();
-        contract.nft_mint(token_id.clone(), get_sample_metadata(), accounts(0).to_string());
+        contract.nft_mint(token_id.clone(), get_sample_metadata(), accounts(0).to_string(), None);
 
         let token = contract.nft_token(token_id.clone()).unwrap();
 
@@ -160,7 +237,7 @@ mod tests {
             .build()
         );
         let token_id = "vbi_nft".to_owned();
-        contract.nft_mint(token_id.clone(), get_sample_metadata(), accounts(0).to_string());
+        contract.nft_mint(token_id.clone(), get_sample_metadata(), accounts(0).to_string(), None);
 
 
         let token = contract.nft_token(token_id.clone()).unwrap();
@@ -177,4 +254,490 @@ mod tests {
         assert_eq!(new_token.token_id, token_id);
         assert_eq!(get_sample_metadata(), new_token.metadata);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_burn_nft() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(0))
+            .build()
+        );
+        let token_id = "vbi_nft".to_owned();
+        contract.nft_mint(token_id.clone(), get_sample_metadata(), accounts(0).to_string(), None);
+        assert_eq!(contract.nft_total_supply(), U128(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .build()
+        );
+        contract.nft_burn(token_id.clone(), None, None);
+
+        assert!(contract.nft_token(token_id.clone()).is_none());
+        assert_eq!(contract.nft_total_supply(), U128(0));
+        assert_eq!(contract.nft_supply_for_owner(accounts(0).to_string()), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only minters can mint tokens")]
+    fn test_mint_not_minter() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        contract.nft_mint("vbi_nft".to_owned(), get_sample_metadata(), accounts(1).to_string(), None);
+    }
+
+    #[test]
+    fn test_mint_by_minter() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.add_minter(accounts(1).to_string());
+        assert!(contract.is_minter(accounts(1).to_string()));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        let token_id = "vbi_nft".to_owned();
+        contract.nft_mint(token_id.clone(), get_sample_metadata(), accounts(1).to_string(), None);
+
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(1).to_string());
+    }
+
+    #[test]
+    fn test_mint_series() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(0))
+            .build()
+        );
+        let series_id = contract.create_series(get_sample_metadata(), Some(2), None, None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build()
+        );
+        contract.nft_mint_series(series_id, accounts(1).to_string());
+        let token_id = contract.nft_mint_series(series_id, accounts(1).to_string());
+        assert_eq!(token_id, format!("{}:2", series_id));
+
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(token.owner_id, accounts(1).to_string());
+        assert_eq!(token.metadata.title, Some("TOKEN_TEST #2".to_owned()));
+        assert_eq!(token.metadata.copies, Some(2));
+        assert_eq!(contract.nft_total_supply(), U128(2));
+    }
+
+    // Series mới do owner accounts(0) tạo
+    fn create_series(context: &mut VMContextBuilder, contract: &mut Contract, price: Option<U128>) -> SeriesId {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(0))
+            .build()
+        );
+        contract.create_series(get_sample_metadata(), None, price, None)
+    }
+
+    // Mint sale của series, toàn bộ tiền bán chuyển cho owner accounts(0)
+    fn mint_sale(series_id: SeriesId, price: Option<U128>, presale: Option<Presale>) -> MintSale {
+        let mut recipients = HashMap::new();
+        recipients.insert(accounts(0).to_string(), MINT_SALE_SHARES);
+
+        MintSale {
+            series_id,
+            price,
+            ft_prices: HashMap::new(),
+            max_supply: 10,
+            max_per_wallet: None,
+            start_at: None,
+            end_at: None,
+            recipients,
+            presale,
+            minted: 0
+        }
+    }
+
+    // Presale 1 token mỗi account với giá 1 yoctoNEAR, kéo dài tới hết sale
+    fn presale(merkle_root: Option<Base64VecU8>) -> Presale {
+        Presale {
+            price: Some(U128(1)),
+            ft_prices: HashMap::new(),
+            max_per_wallet: 1,
+            start_at: None,
+            end_at: U64(u64::MAX),
+            merkle_root
+        }
+    }
+
+    #[test]
+    fn test_nft_buy() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        let series_id = create_series(&mut context, &mut contract, None);
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_mint_sale(Some(MintSale {
+            max_per_wallet: Some(1),
+            ..mint_sale(series_id, Some(U128(MINT_STORAGE_COST)), None)
+        }));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST * 2)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        let token_id = contract.nft_buy(None, None);
+
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(1).to_string());
+        assert_eq!(contract.get_minted_by_account(series_id, accounts(1).to_string()), 1);
+        assert_eq!(contract.get_mint_sale().unwrap().minted, 1);
+
+        // Giới hạn theo ví chỉ áp dụng trong series của đợt bán
+        let next_series_id = create_series(&mut context, &mut contract, None);
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_mint_sale(Some(MintSale {
+            max_per_wallet: Some(1),
+            ..mint_sale(next_series_id, Some(U128(MINT_STORAGE_COST)), None)
+        }));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST * 2)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        contract.nft_buy(None, None);
+        assert_eq!(contract.get_minted_by_account(next_series_id, accounts(1).to_string()), 1);
+    }
+
+    #[test]
+    fn test_nft_buy_with_ft() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        let series_id = create_series(&mut context, &mut contract, None);
+
+        let mut ft_prices = HashMap::new();
+        ft_prices.insert(accounts(3).to_string(), U128(100));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_mint_sale(Some(MintSale {
+            ft_prices,
+            ..mint_sale(series_id, None, None)
+        }));
+
+        // Storage của token được trừ vào storage deposit của buyer
+        testing_env!(context
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        contract.storage_deposit(None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(3))
+            .build()
+        );
+        let unused = contract.ft_on_transfer(accounts(1).to_string(), U128(150), "{}".to_string());
+        assert!(matches!(unused, PromiseOrValue::Value(U128(50))));
+        assert_eq!(contract.nft_supply_for_owner(accounts(1).to_string()), U128(1));
+
+        let storage_balance = contract.storage_balance_of(accounts(1).to_string()).0;
+        assert!(storage_balance > 0 && storage_balance < MINT_STORAGE_COST);
+    }
+
+    #[test]
+    #[should_panic(expected = "Series is sold through mint sale")]
+    fn test_mint_series_during_mint_sale() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        let series_id = create_series(&mut context, &mut contract, Some(U128(1)));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_mint_sale(Some(mint_sale(series_id, Some(U128(MINT_STORAGE_COST)), None)));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        contract.nft_mint_series(series_id, accounts(1).to_string());
+    }
+
+    #[test]
+    fn test_presale_merkle_allowlist() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        let series_id = create_series(&mut context, &mut contract, None);
+
+        // Merkle tree của accounts(1) và accounts(2)
+        let leaf_1 = env::sha256(accounts(1).to_string().as_bytes());
+        let leaf_2 = env::sha256(accounts(2).to_string().as_bytes());
+        let root = if leaf_1 <= leaf_2 {
+            env::sha256(&[leaf_1, leaf_2.clone()].concat())
+        } else {
+            env::sha256(&[leaf_2.clone(), leaf_1].concat())
+        };
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_mint_sale(Some(mint_sale(series_id, Some(U128(MINT_STORAGE_COST)), Some(presale(Some(Base64VecU8(root)))))));
+        assert_eq!(contract.get_mint_phase(), Some(MintPhase::Presale));
+
+        let proof = vec![Base64VecU8(leaf_2)];
+        assert_eq!(contract.get_presale_allocation(accounts(1).to_string(), Some(proof.clone())), 1);
+        assert_eq!(contract.get_presale_allocation(accounts(3).to_string(), Some(proof.clone())), 0);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        let token_id = contract.nft_buy(None, Some(proof.clone()));
+
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(1).to_string());
+        assert_eq!(contract.get_presale_allocation(accounts(1).to_string(), Some(proof)), 0);
+    }
+
+    #[test]
+    fn test_presale_allowlist_per_series() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        let first_series_id = create_series(&mut context, &mut contract, None);
+        let next_series_id = create_series(&mut context, &mut contract, None);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.add_to_allowlist(first_series_id, vec![accounts(1).to_string()], Some(2));
+        assert!(contract.is_allowlisted(first_series_id, accounts(1).to_string()));
+        assert!(!contract.is_allowlisted(next_series_id, accounts(1).to_string()));
+
+        // Presale của series sau không dùng allowlist của series trước
+        contract.set_mint_sale(Some(mint_sale(next_series_id, Some(U128(MINT_STORAGE_COST)), Some(presale(None)))));
+        assert_eq!(contract.get_presale_allocation(accounts(1).to_string(), None), 0);
+
+        contract.add_to_allowlist(next_series_id, vec![accounts(1).to_string()], None);
+        assert_eq!(contract.get_presale_allocation(accounts(1).to_string(), None), 1);
+    }
+
+    // Creator accounts(0) đăng ký key và ký voucher cho token "lazy_nft"
+    fn get_signed_voucher(context: &mut VMContextBuilder, contract: &mut Contract) -> (MintVoucher, Base64VecU8) {
+        use ed25519_dalek::{ExpandedSecretKey, PublicKey, SecretKey};
+
+        let secret_key = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let public_key = PublicKey::from(&secret_key);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build()
+        );
+        contract.register_creator_key(Base64VecU8(public_key.to_bytes().to_vec()));
+
+        let voucher = MintVoucher {
+            token_id: "lazy_nft".to_owned(),
+            creator_id: accounts(0).to_string(),
+            metadata_hash: Base64VecU8(env::sha256(&get_sample_metadata().try_to_vec().unwrap())),
+            price: U128(MINT_STORAGE_COST),
+            royalty: HashMap::new(),
+            expires_at: None,
+            market_id: None
+        };
+        let signature = ExpandedSecretKey::from(&secret_key).sign(&voucher_message(&voucher), &public_key);
+
+        (voucher, Base64VecU8(signature.to_bytes().to_vec()))
+    }
+
+    #[test]
+    fn test_redeem_voucher() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        let (voucher, signature) = get_signed_voucher(&mut context, &mut contract);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST * 2)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        contract.nft_redeem_voucher(voucher, signature, get_sample_metadata(), None);
+
+        let token = contract.nft_token("lazy_nft".to_owned()).unwrap();
+        assert_eq!(token.owner_id, accounts(1).to_string());
+        assert_eq!(token.metadata, get_sample_metadata());
+        assert!(contract.is_voucher_redeemed("lazy_nft".to_owned()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Voucher has already been redeemed")]
+    fn test_redeem_voucher_after_burn() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        let (voucher, signature) = get_signed_voucher(&mut context, &mut contract);
+        let (same_voucher, same_signature) = get_signed_voucher(&mut context, &mut contract);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST * 2)
+            .predecessor_account_id(accounts(1))
+            .build()
+        );
+        contract.nft_redeem_voucher(voucher, signature, get_sample_metadata(), None);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_burn("lazy_nft".to_owned(), None, None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST * 2)
+            .build()
+        );
+        contract.nft_redeem_voucher(same_voucher, same_signature, get_sample_metadata(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Require attached deposit of at least 1 yoctoNear")]
+    fn test_register_creator_key_without_deposit() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.register_creator_key(Base64VecU8(vec![0u8; 32]));
+    }
+
+    #[test]
+    fn test_update_metadata() {
+        let mut context = get_context(false);
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_metadata(accounts(0).to_string());
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build()
+        );
+        let token_id = "vbi_nft".to_owned();
+        contract.nft_mint(token_id.clone(), get_sample_metadata(), accounts(1).to_string(), None);
+
+        let mut metadata = get_sample_metadata();
+        metadata.title = Some("TOKEN_TEST_UPDATED".to_owned());
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .block_timestamp(5_000_000)
+            .build()
+        );
+        contract.nft_update_metadata(token_id.clone(), metadata, None);
+
+        let token = contract.nft_token(token_id.clone()).unwrap();
+        assert_eq!(token.metadata.title, Some("TOKEN_TEST_UPDATED".to_owned()));
+        assert_eq!(token.metadata.updated_at, Some(5));
+
+        testing_env!(context.storage_usage(env::storage_usage()).build());
+        contract.nft_freeze_metadata(token_id.clone());
+        assert!(contract.nft_is_metadata_frozen(token_id));
+    }
+
+    #[test]
+    fn test_migrate() {
+        let context = get_context(false);
+        testing_env!(context.build());
+
+        let old_contract = ContractV1 {
+            owner_id: accounts(0).to_string(),
+            tokens_per_owner: LookupMap::new(StorageKey::TokenPerOwnerKey.try_to_vec().unwrap()),
+            tokens_by_id: LookupMap::new(StorageKey::TokenByIdKey.try_to_vec().unwrap()),
+            token_metadata_by_id: UnorderedMap::new(StorageKey::TokenMetadataByIdKey.try_to_vec().unwrap()),
+            metadata: LazyOption::new(StorageKey::ContractMetadataKey.try_to_vec().unwrap(), None)
+        };
+        env::state_write(&old_contract);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.owner_id, accounts(0).to_string());
+        assert!(contract.transfer_listeners.is_empty());
+        assert!(contract.mint_sale.get().is_none());
+        assert_eq!(contract.next_series_id, 0);
+    }
+
+    #[test]
+    fn test_migrate_from_previous_layout() {
+        let context = get_context(false);
+        testing_env!(context.build());
+
+        let mut old_contract = ContractV2 {
+            owner_id: accounts(0).to_string(),
+            tokens_per_owner: LookupMap::new(StorageKey::TokenPerOwnerKey.try_to_vec().unwrap()),
+            tokens_by_id: LookupMap::new(StorageKey::TokenByIdKey.try_to_vec().unwrap()),
+            token_metadata_by_id: UnorderedMap::new(StorageKey::TokenMetadataByIdKey.try_to_vec().unwrap()),
+            metadata: LazyOption::new(StorageKey::ContractMetadataKey.try_to_vec().unwrap(), None),
+            transfer_listeners: UnorderedSet::new(StorageKey::TransferListenerKey.try_to_vec().unwrap())
+        };
+        old_contract.transfer_listeners.insert(&accounts(1).to_string());
+        env::state_write(&old_contract);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.get_transfer_listeners(), vec![accounts(1).to_string()]);
+    }
+
+    #[test]
+    fn test_migrate_from_voucher_layout() {
+        let context = get_context(false);
+        testing_env!(context.build());
+
+        let old_contract = ContractV1 {
+            owner_id: accounts(0).to_string(),
+            tokens_per_owner: LookupMap::new(StorageKey::TokenPerOwnerKey.try_to_vec().unwrap()),
+            tokens_by_id: LookupMap::new(StorageKey::TokenByIdKey.try_to_vec().unwrap()),
+            token_metadata_by_id: UnorderedMap::new(StorageKey::TokenMetadataByIdKey.try_to_vec().unwrap()),
+            metadata: LazyOption::new(StorageKey::ContractMetadataKey.try_to_vec().unwrap(), None)
+        };
+        let mut old_contract: ContractV7 = ContractV6::from(ContractV5::from(ContractV4::from(ContractV3::from(ContractV2::from(old_contract))))).into();
+        old_contract.creator_keys.insert(&accounts(1).to_string(), &vec![0u8; 32]);
+        old_contract.redeemed_vouchers.insert(&"lazy_nft".to_owned());
+        env::state_write(&old_contract);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.get_creator_key(accounts(1).to_string()), Some(Base64VecU8(vec![0u8; 32])));
+        assert!(contract.is_voucher_redeemed("lazy_nft".to_owned()));
+        assert_eq!(contract.nft_creator("lazy_nft".to_owned()), None);
+        assert!(!contract.nft_is_metadata_frozen("lazy_nft".to_owned()));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::*;
+
+const GAS_FOR_NFT_ON_TOKEN_TRANSFER: Gas = 10_000_000_000_000;
+const NO_DEPOSIT: Balance = 0;
+
+#[ext_contract(ext_transfer_listener)]
+pub trait TransferListener {
+    // Market xoá sale của token khi token được transfer ra ngoài market
+    fn nft_on_token_transfer(
+        &mut self,
+        token_id: TokenId,
+        approval_id: u64,
+        old_owner_id: AccountId,
+        new_owner_id: AccountId
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn add_transfer_listener(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.transfer_listeners.insert(&account_id);
+    }
+
+    #[payable]
+    pub fn remove_transfer_listener(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.transfer_listeners.remove(&account_id);
+    }
+
+    pub fn get_transfer_listeners(&self) -> Vec<AccountId> {
+        self.transfer_listeners.to_vec()
+    }
+
+    // Chỉ thông báo cho listener đã được approve token (có thể đang đăng bán), trừ account thực hiện transfer
+    pub(crate) fn internal_notify_transfer_listeners(&self, sender_id: &AccountId, receiver_id: &AccountId, token_id: &TokenId, previous_token: &Token) {
+        for (account_id, approval_id) in previous_token.approved_account_ids.iter() {
+            if account_id == sender_id || !self.transfer_listeners.contains(account_id) {
+                continue;
+            }
+
+            ext_transfer_listener::nft_on_token_transfer(
+                token_id.clone(),
+                *approval_id,
+                previous_token.owner_id.clone(),
+                receiver_id.clone(),
+                account_id,
+                NO_DEPOSIT,
+                GAS_FOR_NFT_ON_TOKEN_TRANSFER
+            );
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * - Chỉ owner của contract hoặc creator của token được cập nhật metadata, cần deposit ít nhất 1 yoctoNear
+     * - Token đã freeze thì không thể cập nhật
+     * - updated_at được set theo block time
+     * - Metadata lớn hơn thì phải deposit thêm, nhỏ hơn thì được refund storage
+     */
+    #[payable]
+    pub fn nft_update_metadata(&mut self, token_id: TokenId, metadata: TokenMetadata, memo: Option<String>) {
+        // Yêu cầu deposit để function call access key không thể cập nhật metadata
+        assert_at_least_one_yocto();
+        let before_storage_usage = env::storage_usage();
+
+        self.assert_metadata_authority(&token_id);
+        assert!(!self.frozen_token_metadata.contains(&token_id), "Token metadata is frozen");
+
+        let mut metadata = metadata;
+        metadata.updated_at = Some(env::block_timestamp() / 1_000_000);
+        self.token_metadata_by_id.insert(&token_id, &metadata);
+
+        // NFT METADATA UPDATE LOG
+        let nft_metadata_update_log: EventLog = EventLog {
+            standard: "nep171".to_string(),
+            version: "1.1.0".to_string(),
+            event: EventLogVariant::NftMetadataUpdate(vec![ NftMetadataUpdateLog {
+                token_ids: vec![token_id.to_string()],
+                memo
+            } ])
+        };
+        env::log(nft_metadata_update_log.to_string().as_bytes());
+
+        let after_storage_usage = env::storage_usage();
+        if after_storage_usage >= before_storage_usage {
+            // Refund near
+            refund_deposit(after_storage_usage - before_storage_usage);
+        } else {
+            let storage_released = Balance::from(before_storage_usage - after_storage_usage) * env::storage_byte_cost();
+            Promise::new(env::predecessor_account_id()).transfer(storage_released + env::attached_deposit());
+        }
+    }
+
+    // Khoá metadata của token vĩnh viễn
+    #[payable]
+    pub fn nft_freeze_metadata(&mut self, token_id: TokenId) {
+        let before_storage_usage = env::storage_usage();
+
+        self.assert_metadata_authority(&token_id);
+        self.frozen_token_metadata.insert(&token_id);
+
+        let after_storage_usage = env::storage_usage();
+        // Refund near
+        refund_deposit(after_storage_usage - before_storage_usage);
+    }
+
+    pub fn nft_is_metadata_frozen(&self, token_id: TokenId) -> bool {
+        self.frozen_token_metadata.contains(&token_id)
+    }
+
+    pub fn nft_creator(&self, token_id: TokenId) -> Option<AccountId> {
+        self.creator_by_token_id.get(&token_id)
+    }
+
+    pub(crate) fn assert_metadata_authority(&self, token_id: &TokenId) {
+        assert!(self.tokens_by_id.get(token_id).is_some(), "Not found token");
+
+        let sender_id = env::predecessor_account_id();
+        assert!(
+            sender_id == self.owner_id || self.creator_by_token_id.get(token_id) == Some(sender_id),
+            "Only contract owner or token creator can update metadata"
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use crate::*;
+
+// Key lưu state của contract, giống key near_sdk dùng trong env::state_read
+const STATE_KEY: &[u8] = b"STATE";
+
+// Mỗi layout chỉ thêm field vào cuối layout trước, try_from_slice lỗi nếu không đọc hết state
+// nên chỉ đúng 1 layout đọc được state hiện tại
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV1 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>
+}
+
+impl ContractV1 {
+    fn read_state(state: &[u8]) -> Self {
+        Self::try_from_slice(state).expect("Not read state")
+    }
+}
+
+// Thêm transfer listener
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV2 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub transfer_listeners: UnorderedSet<AccountId>
+}
+
+impl ContractV2 {
+    fn read_state(state: &[u8]) -> Self {
+        Self::try_from_slice(state).unwrap_or_else(|_| ContractV1::read_state(state).into())
+    }
+}
+
+impl From<ContractV1> for ContractV2 {
+    fn from(contract: ContractV1) -> Self {
+        Self {
+            owner_id: contract.owner_id,
+            tokens_per_owner: contract.tokens_per_owner,
+            tokens_by_id: contract.tokens_by_id,
+            token_metadata_by_id: contract.token_metadata_by_id,
+            metadata: contract.metadata,
+            transfer_listeners: UnorderedSet::new(StorageKey::TransferListenerKey.try_to_vec().unwrap())
+        }
+    }
+}
+
+// Thêm minter và public mint
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV3 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub transfer_listeners: UnorderedSet<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub public_mint: bool
+}
+
+impl ContractV3 {
+    fn read_state(state: &[u8]) -> Self {
+        Self::try_from_slice(state).unwrap_or_else(|_| ContractV2::read_state(state).into())
+    }
+}
+
+impl From<ContractV2> for ContractV3 {
+    fn from(contract: ContractV2) -> Self {
+        Self {
+            owner_id: contract.owner_id,
+            tokens_per_owner: contract.tokens_per_owner,
+            tokens_by_id: contract.tokens_by_id,
+            token_metadata_by_id: contract.token_metadata_by_id,
+            metadata: contract.metadata,
+            transfer_listeners: contract.transfer_listeners,
+            minters: UnorderedSet::new(StorageKey::MinterKey.try_to_vec().unwrap()),
+            public_mint: false
+        }
+    }
+}
+
+// Thêm series
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV4 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub transfer_listeners: UnorderedSet<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub public_mint: bool,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub next_series_id: SeriesId
+}
+
+impl ContractV4 {
+    fn read_state(state: &[u8]) -> Self {
+        Self::try_from_slice(state).unwrap_or_else(|_| ContractV3::read_state(state).into())
+    }
+}
+
+impl From<ContractV3> for ContractV4 {
+    fn from(contract: ContractV3) -> Self {
+        Self {
+            owner_id: contract.owner_id,
+            tokens_per_owner: contract.tokens_per_owner,
+            tokens_by_id: contract.tokens_by_id,
+            token_metadata_by_id: contract.token_metadata_by_id,
+            metadata: contract.metadata,
+            transfer_listeners: contract.transfer_listeners,
+            minters: contract.minters,
+            public_mint: contract.public_mint,
+            series_by_id: UnorderedMap::new(StorageKey::SeriesByIdKey.try_to_vec().unwrap()),
+            next_series_id: 0
+        }
+    }
+}
+
+// Thêm mint sale
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV5 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub transfer_listeners: UnorderedSet<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub public_mint: bool,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub next_series_id: SeriesId,
+    pub mint_sale: LazyOption<MintSale>,
+    pub minted_by_account: LookupMap<(SeriesId, AccountId), u64>,
+    pub storage_deposits: LookupMap<AccountId, Balance>,
+    pub pending_mint_proceeds: LookupMap<(AccountId, AccountId), Balance>
+}
+
+impl ContractV5 {
+    fn read_state(state: &[u8]) -> Self {
+        Self::try_from_slice(state).unwrap_or_else(|_| ContractV4::read_state(state).into())
+    }
+}
+
+impl From<ContractV4> for ContractV5 {
+    fn from(contract: ContractV4) -> Self {
+        Self {
+            owner_id: contract.owner_id,
+            tokens_per_owner: contract.tokens_per_owner,
+            tokens_by_id: contract.tokens_by_id,
+            token_metadata_by_id: contract.token_metadata_by_id,
+            metadata: contract.metadata,
+            transfer_listeners: contract.transfer_listeners,
+            minters: contract.minters,
+            public_mint: contract.public_mint,
+            series_by_id: contract.series_by_id,
+            next_series_id: contract.next_series_id,
+            mint_sale: LazyOption::new(StorageKey::MintSaleKey.try_to_vec().unwrap(), None),
+            minted_by_account: LookupMap::new(StorageKey::MintedByAccountKey.try_to_vec().unwrap()),
+            storage_deposits: LookupMap::new(StorageKey::StorageDepositKey.try_to_vec().unwrap()),
+            pending_mint_proceeds: LookupMap::new(StorageKey::PendingMintProceedsKey.try_to_vec().unwrap())
+        }
+    }
+}
+
+// Thêm presale allowlist
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV6 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub transfer_listeners: UnorderedSet<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub public_mint: bool,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub next_series_id: SeriesId,
+    pub mint_sale: LazyOption<MintSale>,
+    pub minted_by_account: LookupMap<(SeriesId, AccountId), u64>,
+    pub storage_deposits: LookupMap<AccountId, Balance>,
+    pub pending_mint_proceeds: LookupMap<(AccountId, AccountId), Balance>,
+    pub allowlist: LookupMap<(SeriesId, AccountId), Option<u64>>,
+    pub presale_minted_by_account: LookupMap<(SeriesId, AccountId), u64>
+}
+
+impl ContractV6 {
+    fn read_state(state: &[u8]) -> Self {
+        Self::try_from_slice(state).unwrap_or_else(|_| ContractV5::read_state(state).into())
+    }
+}
+
+impl From<ContractV5> for ContractV6 {
+    fn from(contract: ContractV5) -> Self {
+        Self {
+            owner_id: contract.owner_id,
+            tokens_per_owner: contract.tokens_per_owner,
+            tokens_by_id: contract.tokens_by_id,
+            token_metadata_by_id: contract.token_metadata_by_id,
+            metadata: contract.metadata,
+            transfer_listeners: contract.transfer_listeners,
+            minters: contract.minters,
+            public_mint: contract.public_mint,
+            series_by_id: contract.series_by_id,
+            next_series_id: contract.next_series_id,
+            mint_sale: contract.mint_sale,
+            minted_by_account: contract.minted_by_account,
+            storage_deposits: contract.storage_deposits,
+            pending_mint_proceeds: contract.pending_mint_proceeds,
+            allowlist: LookupMap::new(StorageKey::AllowlistKey.try_to_vec().unwrap()),
+            presale_minted_by_account: LookupMap::new(StorageKey::PresaleMintedByAccountKey.try_to_vec().unwrap())
+        }
+    }
+}
+
+// Thêm voucher
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV7 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub transfer_listeners: UnorderedSet<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub public_mint: bool,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub next_series_id: SeriesId,
+    pub mint_sale: LazyOption<MintSale>,
+    pub minted_by_account: LookupMap<(SeriesId, AccountId), u64>,
+    pub storage_deposits: LookupMap<AccountId, Balance>,
+    pub pending_mint_proceeds: LookupMap<(AccountId, AccountId), Balance>,
+    pub allowlist: LookupMap<(SeriesId, AccountId), Option<u64>>,
+    pub presale_minted_by_account: LookupMap<(SeriesId, AccountId), u64>,
+    pub creator_keys: LookupMap<AccountId, Vec<u8>>,
+    pub redeemed_vouchers: LookupSet<TokenId>
+}
+
+impl ContractV7 {
+    fn read_state(state: &[u8]) -> Self {
+        Self::try_from_slice(state).unwrap_or_else(|_| ContractV6::read_state(state).into())
+    }
+}
+
+impl From<ContractV6> for ContractV7 {
+    fn from(contract: ContractV6) -> Self {
+        Self {
+            owner_id: contract.owner_id,
+            tokens_per_owner: contract.tokens_per_owner,
+            tokens_by_id: contract.tokens_by_id,
+            token_metadata_by_id: contract.token_metadata_by_id,
+            metadata: contract.metadata,
+            transfer_listeners: contract.transfer_listeners,
+            minters: contract.minters,
+            public_mint: contract.public_mint,
+            series_by_id: contract.series_by_id,
+            next_series_id: contract.next_series_id,
+            mint_sale: contract.mint_sale,
+            minted_by_account: contract.minted_by_account,
+            storage_deposits: contract.storage_deposits,
+            pending_mint_proceeds: contract.pending_mint_proceeds,
+            allowlist: contract.allowlist,
+            presale_minted_by_account: contract.presale_minted_by_account,
+            creator_keys: LookupMap::new(StorageKey::CreatorKeyKey.try_to_vec().unwrap()),
+            redeemed_vouchers: LookupSet::new(StorageKey::RedeemedVoucherKey.try_to_vec().unwrap())
+        }
+    }
+}
+
+impl From<ContractV7> for Contract {
+    fn from(contract: ContractV7) -> Self {
+        // Giữ nguyên state cũ, các tính năng mới bắt đầu với state rỗng
+        Self {
+            owner_id: contract.owner_id,
+            tokens_per_owner: contract.tokens_per_owner,
+            tokens_by_id: contract.tokens_by_id,
+            token_metadata_by_id: contract.token_metadata_by_id,
+            metadata: contract.metadata,
+            transfer_listeners: contract.transfer_listeners,
+            minters: contract.minters,
+            public_mint: contract.public_mint,
+            series_by_id: contract.series_by_id,
+            next_series_id: contract.next_series_id,
+            mint_sale: contract.mint_sale,
+            minted_by_account: contract.minted_by_account,
+            storage_deposits: contract.storage_deposits,
+            pending_mint_proceeds: contract.pending_mint_proceeds,
+            allowlist: contract.allowlist,
+            presale_minted_by_account: contract.presale_minted_by_account,
+            creator_keys: contract.creator_keys,
+            redeemed_vouchers: contract.redeemed_vouchers,
+            creator_by_token_id: LookupMap::new(StorageKey::CreatorByTokenIdKey.try_to_vec().unwrap()),
+            frozen_token_metadata: LookupSet::new(StorageKey::FrozenTokenMetadataKey.try_to_vec().unwrap())
+        }
+    }
+}
+
+impl Contract {
+    // Đọc state theo layout đang deploy và migrate qua từng layout tới layout hiện tại
+    pub(crate) fn internal_migrate() -> Self {
+        let state = env::storage_read(STATE_KEY).expect("Not read state");
+        ContractV7::read_state(&state).into()
+    }
+}
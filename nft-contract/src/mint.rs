@@ -7,6 +7,7 @@ use crate::*;
 #[near_bindgen]
 impl Contract {
     /**
+     * - Chỉ owner và minter được mint (trừ khi bật public mint)
      * - Yêu cầu user nạp tiền để cover phí lưu trữ
      * - Thêm token vào token_by_id
      * - Thêm token metadata
@@ -15,6 +16,7 @@ impl Contract {
      */
     #[payable]
     pub fn nft_mint(&mut self, token_id: TokenId, metadata: TokenMetadata, receiver_id: AccountId, perpetual_royalties: Option<HashMap<AccountId, u32>>) {
+        self.assert_minter();
         let before_storage_usage = env::storage_usage();
 
         let mut royalty = HashMap::new();
@@ -30,34 +32,10 @@ impl Contract {
             }
         }
 
-        let token = Token {
-            owner_id: receiver_id,
-            approved_account_ids: HashMap::default(),
-            next_approval_id: 0,
-            royalty
-        };
+        // Token id dạng "series_id:edition" dành cho edition của series
+        assert!(!token_id.contains(SERIES_DELIMITER), "Token id can not contain {}", SERIES_DELIMITER);
 
-        assert!(
-            self.tokens_by_id.insert(&token_id, &token).is_none(),
-            "Token already exsits"
-        );
-
-        self.token_metadata_by_id.insert(&token_id, &metadata);
-
-        // set token per owner
-        self.internal_add_token_to_owner(&token_id, &token.owner_id);
-
-        // NFT MINT LOG
-        let nft_mint_log: EventLog = EventLog {
-            standard: "nep171".to_string(),
-            version: "1.0.0".to_string(),
-            event: EventLogVariant::NftMint(vec![ NftMintLog {
-                owner_id: token.owner_id.to_string(),
-                token_ids: vec![token_id.to_string()],
-                memo: None
-            } ])
-        };
-        env::log(&nft_mint_log.to_string().as_bytes());
+        self.internal_mint(&token_id, metadata, &receiver_id, &env::predecessor_account_id(), royalty);
 
         let after_storage_usage = env::storage_usage();
         // Refund near
@@ -68,7 +46,12 @@ impl Contract {
         let token = self.tokens_by_id.get(&token_id);
 
         if let Some(token) = token {
-            let metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+            let mut metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+
+            // Edition của series lấy metadata từ series
+            if let Some((series, edition)) = self.internal_series_of(&token_id) {
+                metadata = series.edition_metadata(edition, metadata);
+            }
 
             Some(JsonToken {
                 owner_id: token.owner_id,
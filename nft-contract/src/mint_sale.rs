@@ -0,0 +1,431 @@
+use crate::*;
+
+const GAS_FOR_FT_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_MINT_PROCEEDS: Gas = 5_000_000_000_000;
+//constant used to attach 0 NEAR to a call
+const NO_DEPOSIT: Balance = 0;
+// 10_000 basis points = 100%
+pub const MINT_SALE_SHARES: u32 = 10_000;
+
+// Đợt bán (primary sale) các edition của 1 series, do owner cấu hình
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintSale {
+    pub series_id: SeriesId,
+    pub price: Option<U128>, // giá NEAR, None nếu không bán bằng NEAR
+    pub ft_prices: HashMap<AccountId, U128>, // giá theo FT contract id
+    pub max_supply: u64,
+    pub max_per_wallet: Option<u64>,
+    pub start_at: Option<U64>, // nanoseconds
+    pub end_at: Option<U64>, // nanoseconds
+    pub recipients: HashMap<AccountId, u32>, // basis points, tổng bằng 10_000
+    pub presale: Option<Presale>, // giai đoạn allowlist trước public sale
+    #[serde(default)]
+    pub minted: u64
+}
+
+// Giai đoạn presale chỉ dành cho account trong allowlist on-chain hoặc chứng minh được bằng merkle proof
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Presale {
+    pub price: Option<U128>, // giá NEAR presale
+    pub ft_prices: HashMap<AccountId, U128>,
+    pub max_per_wallet: u64, // allocation mặc định của mỗi account
+    pub start_at: Option<U64>, // nanoseconds
+    pub end_at: U64, // nanoseconds, public sale bắt đầu sau thời điểm này
+    pub merkle_root: Option<Base64VecU8> // sha256 merkle root của các account id
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MintPhase {
+    NotStarted,
+    Presale,
+    Public,
+    Ended
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintSaleArgs {
+    pub receiver_id: Option<AccountId>,
+    pub proof: Option<Vec<Base64VecU8>>
+}
+
+impl MintSale {
+    pub(crate) fn phase(&self, timestamp: u64) -> MintPhase {
+        if let Some(presale) = &self.presale {
+            if presale.start_at.map(| start_at | timestamp < start_at.0).unwrap_or(false) {
+                return MintPhase::NotStarted;
+            }
+            if timestamp < presale.end_at.0 {
+                return MintPhase::Presale;
+            }
+        }
+
+        if self.start_at.map(| start_at | timestamp < start_at.0).unwrap_or(false) {
+            MintPhase::NotStarted
+        } else if self.end_at.map(| end_at | timestamp >= end_at.0).unwrap_or(false) {
+            MintPhase::Ended
+        } else {
+            MintPhase::Public
+        }
+    }
+}
+
+fn internal_mint_price(price: Option<U128>, ft_prices: &HashMap<AccountId, U128>, ft_contract_id: Option<&AccountId>) -> Balance {
+    if let Some(ft_contract_id) = ft_contract_id {
+        ft_prices.get(ft_contract_id).expect("Mint sale does not accept this token").0
+    } else {
+        price.expect("Mint sale does not accept NEAR").0
+    }
+}
+
+// Leaf là sha256(account_id), mỗi cặp node được sắp xếp trước khi hash
+pub(crate) fn verify_merkle_proof(root: &[u8], account_id: &AccountId, proof: &[Base64VecU8]) -> bool {
+    let mut hash = env::sha256(account_id.as_bytes());
+
+    for sibling in proof {
+        let sibling = &sibling.0;
+        hash = if hash.as_slice() <= sibling.as_slice() {
+            env::sha256(&[hash.as_slice(), sibling].concat())
+        } else {
+            env::sha256(&[sibling.as_slice(), hash.as_slice()].concat())
+        };
+    }
+
+    hash.as_slice() == root
+}
+
+#[ext_contract(ext_ft_contract)]
+pub trait FungibleTokenCore {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[ext_contract(ext_mint_sale_resolver)]
+pub trait MintSaleResolver {
+    fn resolve_mint_proceeds(&mut self, account_id: AccountId, ft_contract_id: AccountId, amount: U128);
+}
+
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128>;
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    // Mua 1 token bằng FT, msg: {"receiver_id": ...}. Phần FT thừa được trả lại cho buyer
+    // Phí storage của token được trừ vào storage deposit của buyer
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let before_storage_usage = env::storage_usage();
+        let ft_contract_id = env::predecessor_account_id();
+        let args: MintSaleArgs = near_sdk::serde_json::from_str(&msg).expect("Not valid mint sale args");
+
+        let mint_sale = self.mint_sale.get().expect("Mint sale is not active");
+
+        let receiver_id = args.receiver_id.unwrap_or_else(|| sender_id.clone());
+        let (_, price) = self.internal_mint_from_sale(&sender_id, &receiver_id, Some(&ft_contract_id), args.proof);
+        assert!(amount.0 >= price, "Amount must be greater than or equal price: {}", price);
+
+        let after_storage_usage = env::storage_usage();
+        let storage_cost = env::storage_byte_cost() * Balance::from(after_storage_usage - before_storage_usage);
+        self.internal_charge_storage_deposit(&sender_id, storage_cost);
+
+        self.internal_distribute_mint_proceeds(&mint_sale.recipients, Some(ft_contract_id), price);
+
+        PromiseOrValue::Value(U128(amount.0 - price))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Owner mở/cập nhật đợt bán, None để đóng
+    #[payable]
+    pub fn set_mint_sale(&mut self, mint_sale: Option<MintSale>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let mut mint_sale = if let Some(mint_sale) = mint_sale {
+            mint_sale
+        } else {
+            self.mint_sale.remove();
+            return;
+        };
+
+        assert!(self.series_by_id.get(&mint_sale.series_id).is_some(), "Not found series");
+        assert!(mint_sale.price.is_some() || !mint_sale.ft_prices.is_empty(), "Mint sale must have at least one price");
+        assert!(!mint_sale.recipients.is_empty(), "Mint sale must have at least one recipient");
+        assert_eq!(mint_sale.recipients.values().sum::<u32>(), MINT_SALE_SHARES, "Recipient shares must sum to {}", MINT_SALE_SHARES);
+        if let (Some(start_at), Some(end_at)) = (mint_sale.start_at, mint_sale.end_at) {
+            assert!(end_at.0 > start_at.0, "end_at must be greater than start_at");
+        }
+        if let Some(presale) = &mint_sale.presale {
+            assert!(presale.price.is_some() || !presale.ft_prices.is_empty(), "Presale must have at least one price");
+            if let Some(start_at) = presale.start_at {
+                assert!(presale.end_at.0 > start_at.0, "Presale end_at must be greater than start_at");
+            }
+            if let Some(start_at) = mint_sale.start_at {
+                assert!(start_at.0 >= presale.end_at.0, "Public sale must start after presale");
+            }
+        }
+
+        // Cập nhật cấu hình của cùng series thì giữ lại số lượng đã bán
+        mint_sale.minted = self.mint_sale.get()
+            .filter(| old | old.series_id == mint_sale.series_id)
+            .map(| old | old.minted)
+            .unwrap_or(0);
+
+        self.mint_sale.set(&mint_sale);
+    }
+
+    pub fn get_mint_sale(&self) -> Option<MintSale> {
+        self.mint_sale.get()
+    }
+
+    pub fn get_minted_by_account(&self, series_id: SeriesId, account_id: AccountId) -> u64 {
+        self.minted_by_account.get(&(series_id, account_id)).unwrap_or(0)
+    }
+
+    // Nạp NEAR trước để trả phí storage khi mua bằng FT
+    #[payable]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>) {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let deposit = env::attached_deposit();
+        assert!(deposit > 0, "Attached deposit must be greater than 0");
+
+        let balance = self.storage_deposits.get(&account_id).unwrap_or(0);
+        self.storage_deposits.insert(&account_id, &(balance + deposit));
+    }
+
+    // Rút toàn bộ storage deposit chưa dùng
+    #[payable]
+    pub fn storage_withdraw(&mut self) -> U128 {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+
+        let amount = self.storage_deposits.remove(&account_id).unwrap_or(0);
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+
+        U128(amount)
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.storage_deposits.get(&account_id).unwrap_or(0))
+    }
+
+    // Tiền bán bằng FT chưa chuyển được cho recipient (vd. chưa đăng ký storage trên FT contract)
+    pub fn get_pending_mint_proceeds(&self, account_id: AccountId, ft_contract_id: AccountId) -> U128 {
+        U128(self.pending_mint_proceeds.get(&(account_id, ft_contract_id)).unwrap_or(0))
+    }
+
+    // Recipient nhận lại tiền bán bằng FT bị chuyển lỗi trước đó
+    #[payable]
+    pub fn claim_mint_proceeds(&mut self, ft_contract_id: AccountId) -> U128 {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+
+        let amount = self.pending_mint_proceeds.remove(&(account_id.clone(), ft_contract_id.clone())).expect("Nothing to claim");
+        self.internal_transfer_mint_proceeds(account_id, ft_contract_id, amount);
+
+        U128(amount)
+    }
+
+    // ft_transfer lỗi thì giữ lại phần tiền để recipient claim sau
+    #[private]
+    pub fn resolve_mint_proceeds(&mut self, account_id: AccountId, ft_contract_id: AccountId, amount: U128) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return;
+        }
+
+        let key = (account_id, ft_contract_id);
+        let pending = self.pending_mint_proceeds.get(&key).unwrap_or(0);
+        self.pending_mint_proceeds.insert(&key, &(pending + amount.0));
+    }
+
+    pub fn get_mint_phase(&self) -> Option<MintPhase> {
+        self.mint_sale.get().map(| mint_sale | mint_sale.phase(env::block_timestamp()))
+    }
+
+    // Owner thêm account vào allowlist on-chain của series, allocation mặc định là max_per_wallet của presale
+    #[payable]
+    pub fn add_to_allowlist(&mut self, series_id: SeriesId, account_ids: Vec<AccountId>, allocation: Option<u64>) {
+        assert_one_yocto();
+        self.assert_owner();
+        assert!(self.series_by_id.get(&series_id).is_some(), "Not found series");
+
+        for account_id in account_ids {
+            self.allowlist.insert(&(series_id, account_id), &allocation);
+        }
+    }
+
+    #[payable]
+    pub fn remove_from_allowlist(&mut self, series_id: SeriesId, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        for account_id in account_ids {
+            self.allowlist.remove(&(series_id, account_id));
+        }
+    }
+
+    pub fn is_allowlisted(&self, series_id: SeriesId, account_id: AccountId) -> bool {
+        self.allowlist.get(&(series_id, account_id)).is_some()
+    }
+
+    // Số token account còn được mua trong presale hiện tại, proof dùng khi account nằm trong merkle allowlist
+    pub fn get_presale_allocation(&self, account_id: AccountId, proof: Option<Vec<Base64VecU8>>) -> u64 {
+        let mint_sale = if let Some(mint_sale) = self.mint_sale.get() {
+            mint_sale
+        } else {
+            return 0;
+        };
+        let presale = if let Some(presale) = mint_sale.presale {
+            presale
+        } else {
+            return 0;
+        };
+
+        let allocation = self.internal_presale_allocation(&presale, mint_sale.series_id, &account_id, proof);
+        allocation.saturating_sub(self.presale_minted_by_account.get(&(mint_sale.series_id, account_id)).unwrap_or(0))
+    }
+
+    /**
+     * - Mua 1 token bằng NEAR, deposit phải cover giá và phí storage
+     * - Trong presale phải nằm trong allowlist (hoặc gửi merkle proof)
+     * - Tiền bán được chia cho các recipient
+     * - Refund lại NEAR user deposit thừa
+     */
+    #[payable]
+    pub fn nft_buy(&mut self, receiver_id: Option<AccountId>, proof: Option<Vec<Base64VecU8>>) -> TokenId {
+        let before_storage_usage = env::storage_usage();
+        let buyer_id = env::predecessor_account_id();
+
+        let mint_sale = self.mint_sale.get().expect("Mint sale is not active");
+
+        let receiver_id = receiver_id.unwrap_or_else(|| buyer_id.clone());
+        let (token_id, price) = self.internal_mint_from_sale(&buyer_id, &receiver_id, None, proof);
+
+        let after_storage_usage = env::storage_usage();
+        let storage_cost = env::storage_byte_cost() * Balance::from(after_storage_usage - before_storage_usage);
+        let attached_deposit = env::attached_deposit();
+
+        assert!(
+            storage_cost + price <= attached_deposit,
+            "Must attach {} yoctoNear to cover price and storage", storage_cost + price
+        );
+
+        self.internal_distribute_mint_proceeds(&mint_sale.recipients, None, price);
+
+        let refund = attached_deposit - storage_cost - price;
+        if refund > 1 {
+            Promise::new(buyer_id).transfer(refund);
+        }
+
+        token_id
+    }
+
+    // Kiểm tra phase, supply, giới hạn theo ví rồi mint edition tiếp theo, trả về token id và giá theo currency (None là NEAR)
+    pub(crate) fn internal_mint_from_sale(&mut self, buyer_id: &AccountId, receiver_id: &AccountId, ft_contract_id: Option<&AccountId>, proof: Option<Vec<Base64VecU8>>) -> (TokenId, Balance) {
+        let mut mint_sale = self.mint_sale.get().expect("Mint sale is not active");
+        assert!(mint_sale.minted < mint_sale.max_supply, "Mint sale is sold out");
+
+        let price = match mint_sale.phase(env::block_timestamp()) {
+            MintPhase::NotStarted => env::panic("Mint sale has not started yet".as_bytes()),
+            MintPhase::Ended => env::panic("Mint sale has ended".as_bytes()),
+            MintPhase::Presale => {
+                let presale = mint_sale.presale.as_ref().unwrap();
+
+                let allocation = self.internal_presale_allocation(presale, mint_sale.series_id, buyer_id, proof);
+                assert!(allocation > 0, "Account is not in presale allowlist");
+
+                // Allowlist và allocation đã dùng tính riêng cho từng series
+                let presale_key = (mint_sale.series_id, buyer_id.clone());
+                let presale_minted = self.presale_minted_by_account.get(&presale_key).unwrap_or(0);
+                assert!(presale_minted < allocation, "Account has reached presale limit of {}", allocation);
+                self.presale_minted_by_account.insert(&presale_key, &(presale_minted + 1));
+
+                internal_mint_price(presale.price, &presale.ft_prices, ft_contract_id)
+            },
+            MintPhase::Public => internal_mint_price(mint_sale.price, &mint_sale.ft_prices, ft_contract_id)
+        };
+
+        // Giới hạn theo ví tính riêng cho từng series
+        let minted_key = (mint_sale.series_id, buyer_id.clone());
+        let minted_by_buyer = self.minted_by_account.get(&minted_key).unwrap_or(0);
+        if let Some(max_per_wallet) = mint_sale.max_per_wallet {
+            assert!(minted_by_buyer < max_per_wallet, "Account has reached mint limit of {}", max_per_wallet);
+        }
+
+        mint_sale.minted += 1;
+        self.mint_sale.set(&mint_sale);
+        self.minted_by_account.insert(&minted_key, &(minted_by_buyer + 1));
+
+        (self.internal_mint_edition(mint_sale.series_id, receiver_id), price)
+    }
+
+    // Allocation presale của account: allowlist on-chain trước, sau đó tới merkle proof
+    pub(crate) fn internal_presale_allocation(&self, presale: &Presale, series_id: SeriesId, account_id: &AccountId, proof: Option<Vec<Base64VecU8>>) -> u64 {
+        if let Some(allocation) = self.allowlist.get(&(series_id, account_id.clone())) {
+            return allocation.unwrap_or(presale.max_per_wallet);
+        }
+
+        match (&presale.merkle_root, proof) {
+            (Some(root), Some(proof)) if verify_merkle_proof(&root.0, account_id, &proof) => presale.max_per_wallet,
+            _ => 0
+        }
+    }
+
+    // Chia tiền bán theo basis points, phần lẻ do làm tròn thuộc về owner
+    pub(crate) fn internal_distribute_mint_proceeds(&self, recipients: &HashMap<AccountId, u32>, ft_contract_id: Option<AccountId>, amount: Balance) {
+        let mut shares: Vec<(AccountId, Balance)> = recipients.iter()
+            .map(| (account_id, share) | (account_id.clone(), amount * u128::from(*share) / u128::from(MINT_SALE_SHARES)))
+            .collect();
+        let remainder = amount - shares.iter().map(| (_, share) | share).sum::<Balance>();
+        shares.push((self.owner_id.clone(), remainder));
+
+        for (account_id, share) in shares {
+            if share == 0 {
+                continue;
+            }
+
+            if let Some(ft_contract_id) = ft_contract_id.as_ref() {
+                self.internal_transfer_mint_proceeds(account_id, ft_contract_id.clone(), share);
+            } else {
+                Promise::new(account_id).transfer(share);
+            }
+        }
+    }
+
+    pub(crate) fn internal_transfer_mint_proceeds(&self, account_id: AccountId, ft_contract_id: AccountId, amount: Balance) {
+        ext_ft_contract::ft_transfer(
+            account_id.clone(),
+            U128(amount),
+            None,
+            &ft_contract_id,
+            1,
+            GAS_FOR_FT_TRANSFER
+        ).then(ext_mint_sale_resolver::resolve_mint_proceeds(
+            account_id,
+            ft_contract_id,
+            U128(amount),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_MINT_PROCEEDS
+        ));
+    }
+
+    pub(crate) fn internal_charge_storage_deposit(&mut self, account_id: &AccountId, storage_cost: Balance) {
+        let balance = self.storage_deposits.get(account_id).unwrap_or(0);
+        assert!(
+            balance >= storage_cost,
+            "Must deposit {} yoctoNear with storage_deposit to cover storage", storage_cost
+        );
+
+        if balance == storage_cost {
+            self.storage_deposits.remove(account_id);
+        } else {
+            self.storage_deposits.insert(account_id, &(balance - storage_cost));
+        }
+    }
+}
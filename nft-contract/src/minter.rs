@@ -0,0 +1,54 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    // Owner cấp quyền mint cho account
+    #[payable]
+    pub fn add_minter(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.minters.insert(&account_id);
+    }
+
+    #[payable]
+    pub fn remove_minter(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.minters.remove(&account_id);
+    }
+
+    // Bật public mint thì ai cũng có thể mint
+    #[payable]
+    pub fn set_public_mint(&mut self, public_mint: bool) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.public_mint = public_mint;
+    }
+
+    pub fn get_minters(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.minters.iter()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .collect()
+    }
+
+    pub fn is_minter(&self, account_id: AccountId) -> bool {
+        account_id == self.owner_id || self.minters.contains(&account_id)
+    }
+
+    pub fn is_public_mint(&self) -> bool {
+        self.public_mint
+    }
+
+    pub(crate) fn assert_minter(&self) {
+        assert!(
+            self.public_mint || self.is_minter(env::predecessor_account_id()),
+            "Only minters can mint tokens"
+        );
+    }
+}
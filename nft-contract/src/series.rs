@@ -0,0 +1,180 @@
+use crate::*;
+
+pub type SeriesId = u64;
+// token_id của edition có dạng "series_id:edition"
+pub const SERIES_DELIMITER: char = ':';
+
+// Metadata dùng chung cho tất cả edition của series
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Series {
+    pub series_id: SeriesId,
+    pub creator_id: AccountId,
+    pub metadata: TokenMetadata,
+    pub max_copies: Option<u64>,
+    pub price: Option<U128>, // None thì chỉ creator được mint
+    pub copies_minted: u64,
+    pub royalty: HashMap<AccountId, u32>
+}
+
+impl Series {
+    // Ghép metadata của series với data riêng của edition, data của edition được ưu tiên
+    pub(crate) fn edition_metadata(&self, edition: u64, token_metadata: TokenMetadata) -> TokenMetadata {
+        let template = self.metadata.clone();
+
+        TokenMetadata {
+            title: token_metadata.title.or(template.title.map(| title | format!("{} #{}", title, edition))),
+            description: token_metadata.description.or(template.description),
+            media: token_metadata.media.or(template.media),
+            media_hash: token_metadata.media_hash.or(template.media_hash),
+            copies: token_metadata.copies.or(self.max_copies).or(template.copies),
+            issued_at: token_metadata.issued_at.or(template.issued_at),
+            expires_at: token_metadata.expires_at.or(template.expires_at),
+            starts_at: token_metadata.starts_at.or(template.starts_at),
+            updated_at: token_metadata.updated_at.or(template.updated_at),
+            extra: token_metadata.extra.or(template.extra),
+            reference: token_metadata.reference.or(template.reference),
+            reference_hash: token_metadata.reference_hash.or(template.reference_hash)
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /**
+     * - Chỉ minter được tạo series
+     * - Metadata được lưu 1 lần cho tất cả edition
+     * - Refund lại NEAR user deposit thừa
+     */
+    #[payable]
+    pub fn create_series(&mut self, metadata: TokenMetadata, max_copies: Option<u64>, price: Option<U128>, perpetual_royalties: Option<HashMap<AccountId, u32>>) -> SeriesId {
+        self.assert_minter();
+        let before_storage_usage = env::storage_usage();
+
+        if let Some(max_copies) = max_copies {
+            assert!(max_copies > 0, "max_copies must be greater than 0");
+        }
+
+        let royalty = perpetual_royalties.unwrap_or_default();
+        assert!(royalty.len() < 7, "Cannot add more than 6 perpetual royalty amounts");
+
+        let series_id = self.next_series_id;
+        self.next_series_id += 1;
+
+        self.series_by_id.insert(&series_id, &Series {
+            series_id,
+            creator_id: env::predecessor_account_id(),
+            metadata,
+            max_copies,
+            price,
+            copies_minted: 0,
+            royalty
+        });
+
+        let after_storage_usage = env::storage_usage();
+        // Refund near
+        refund_deposit(after_storage_usage - before_storage_usage);
+
+        series_id
+    }
+
+    // Creator cập nhật giá bán edition, None thì chỉ creator được mint
+    #[payable]
+    pub fn set_series_price(&mut self, series_id: SeriesId, price: Option<U128>) {
+        assert_one_yocto();
+
+        let mut series = self.series_by_id.get(&series_id).expect("Not found series");
+        assert_eq!(env::predecessor_account_id(), series.creator_id, "Only series creator can update price");
+
+        series.price = price;
+        self.series_by_id.insert(&series_id, &series);
+    }
+
+    /**
+     * - Creator mint miễn phí, người khác trả price cho creator (nếu series đang bán và không có mint sale)
+     * - Edition chỉ lưu data riêng (issued_at), còn lại lấy từ series
+     * - Refund lại NEAR user deposit thừa
+     */
+    #[payable]
+    pub fn nft_mint_series(&mut self, series_id: SeriesId, receiver_id: AccountId) -> TokenId {
+        let before_storage_usage = env::storage_usage();
+        let series = self.series_by_id.get(&series_id).expect("Not found series");
+
+        let price = if env::predecessor_account_id() == series.creator_id {
+            0
+        } else {
+            // Series đang có mint sale thì phải mua qua nft_buy để áp dụng phase, allowlist và giới hạn theo ví
+            assert!(
+                self.mint_sale.get().map(| mint_sale | mint_sale.series_id != series_id).unwrap_or(true),
+                "Series is sold through mint sale, use nft_buy"
+            );
+            series.price.expect("Series is not for sale").0
+        };
+
+        let token_id = self.internal_mint_edition(series_id, &receiver_id);
+
+        let after_storage_usage = env::storage_usage();
+        let storage_cost = env::storage_byte_cost() * Balance::from(after_storage_usage - before_storage_usage);
+        let attached_deposit = env::attached_deposit();
+
+        assert!(
+            storage_cost + price <= attached_deposit,
+            "Must attach {} yoctoNear to cover price and storage", storage_cost + price
+        );
+
+        if price > 0 {
+            Promise::new(series.creator_id).transfer(price);
+        }
+
+        let refund = attached_deposit - storage_cost - price;
+        if refund > 1 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+
+        token_id
+    }
+
+    pub fn get_series(&self, series_id: SeriesId) -> Option<Series> {
+        self.series_by_id.get(&series_id)
+    }
+
+    pub fn get_series_list(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Series> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.series_by_id.values()
+        .skip(start as usize)
+        .take(limit.unwrap_or(0) as usize)
+        .collect()
+    }
+
+    // Mint edition tiếp theo của series
+    pub(crate) fn internal_mint_edition(&mut self, series_id: SeriesId, receiver_id: &AccountId) -> TokenId {
+        let mut series = self.series_by_id.get(&series_id).expect("Not found series");
+
+        if let Some(max_copies) = series.max_copies {
+            assert!(series.copies_minted < max_copies, "Series is sold out");
+        }
+
+        series.copies_minted += 1;
+        let edition = series.copies_minted;
+        let token_id = format!("{}{}{}", series_id, SERIES_DELIMITER, edition);
+
+        let token_metadata = TokenMetadata {
+            issued_at: Some(env::block_timestamp() / 1_000_000),
+            ..Default::default()
+        };
+        self.internal_mint(&token_id, token_metadata, receiver_id, &series.creator_id, series.royalty.clone());
+        self.series_by_id.insert(&series_id, &series);
+
+        token_id
+    }
+
+    // Series và edition của token nếu token được mint từ series
+    pub(crate) fn internal_series_of(&self, token_id: &TokenId) -> Option<(Series, u64)> {
+        let (series_id, edition) = token_id.split_once(SERIES_DELIMITER)?;
+        let series_id: SeriesId = series_id.parse().ok()?;
+        let edition: u64 = edition.parse().ok()?;
+
+        self.series_by_id.get(&series_id).map(| series | (series, edition))
+    }
+}
@@ -0,0 +1,116 @@
+use crate::*;
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use std::convert::TryFrom;
+
+// Voucher do creator ký off-chain (ed25519) cho token chưa được mint
+// Chữ ký là trên borsh của (nft contract id, voucher), metadata_hash là sha256 borsh của TokenMetadata
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintVoucher {
+    pub token_id: TokenId,
+    pub creator_id: AccountId,
+    pub metadata_hash: Base64VecU8,
+    pub price: U128, // NEAR
+    pub royalty: HashMap<AccountId, u32>,
+    pub expires_at: Option<U64>, // nanoseconds
+    pub market_id: Option<AccountId> // chỉ market này được redeem, market tự thu price và trả cho creator
+}
+
+// Gắn nft contract id vào dữ liệu được ký để voucher không dùng được trên contract khác
+pub(crate) fn voucher_message(voucher: &MintVoucher) -> Vec<u8> {
+    (env::current_account_id(), voucher).try_to_vec().unwrap()
+}
+
+#[near_bindgen]
+impl Contract {
+    // Minter đăng ký public key (32 bytes) dùng để ký voucher
+    #[payable]
+    pub fn register_creator_key(&mut self, public_key: Base64VecU8) {
+        assert_at_least_one_yocto();
+        self.assert_minter();
+        let before_storage_usage = env::storage_usage();
+
+        assert!(PublicKey::from_bytes(&public_key.0).is_ok(), "Invalid ed25519 public key");
+        self.creator_keys.insert(&env::predecessor_account_id(), &public_key.0);
+
+        let after_storage_usage = env::storage_usage();
+        // Refund near
+        refund_deposit(after_storage_usage.saturating_sub(before_storage_usage));
+    }
+
+    #[payable]
+    pub fn remove_creator_key(&mut self) {
+        assert_one_yocto();
+
+        self.creator_keys.remove(&env::predecessor_account_id()).expect("Not found creator key");
+    }
+
+    pub fn get_creator_key(&self, account_id: AccountId) -> Option<Base64VecU8> {
+        self.creator_keys.get(&account_id).map(Base64VecU8)
+    }
+
+    pub fn is_voucher_redeemed(&self, token_id: TokenId) -> bool {
+        self.redeemed_vouchers.contains(&token_id)
+    }
+
+    /**
+     * - Kiểm tra chữ ký của creator và metadata khớp với voucher
+     * - Mỗi token id chỉ được redeem 1 lần, kể cả khi token đã bị burn
+     * - Mint token cho receiver, tiền bán được chuyển cho creator
+     * - Voucher bán qua market: chỉ market được redeem, deposit chỉ cần cover storage và phần thừa được refund cho receiver
+     * - Deposit phải cover giá và phí storage, trả về số NEAR được refund
+     */
+    #[payable]
+    pub fn nft_redeem_voucher(&mut self, voucher: MintVoucher, signature: Base64VecU8, metadata: TokenMetadata, receiver_id: Option<AccountId>) -> U128 {
+        let before_storage_usage = env::storage_usage();
+
+        let public_key = self.creator_keys.get(&voucher.creator_id).expect("Creator has no registered key");
+        let public_key = PublicKey::from_bytes(&public_key).expect("Invalid ed25519 public key");
+        let signature = Signature::try_from(signature.0.as_slice()).expect("Invalid ed25519 signature");
+        assert!(
+            public_key.verify(&voucher_message(&voucher), &signature).is_ok(),
+            "Voucher signature is not valid"
+        );
+        assert!(self.redeemed_vouchers.insert(&voucher.token_id), "Voucher has already been redeemed");
+
+        assert_eq!(env::sha256(&metadata.try_to_vec().unwrap()), voucher.metadata_hash.0, "Metadata does not match voucher");
+        if let Some(expires_at) = voucher.expires_at {
+            assert!(env::block_timestamp() < expires_at.0, "Voucher has expired");
+        }
+        assert!(voucher.royalty.len() < 7, "Cannot add more than 6 perpetual royalty amounts");
+
+        // Token id dạng "series_id:edition" dành cho edition của series
+        assert!(!voucher.token_id.contains(SERIES_DELIMITER), "Token id can not contain {}", SERIES_DELIMITER);
+
+        let predecessor_id = env::predecessor_account_id();
+        let is_market_sale = voucher.market_id.is_some();
+        if let Some(market_id) = voucher.market_id.as_ref() {
+            assert_eq!(&predecessor_id, market_id, "Voucher can only be redeemed through its market");
+        }
+
+        let receiver_id = receiver_id.unwrap_or_else(|| predecessor_id.clone());
+        self.internal_mint(&voucher.token_id, metadata, &receiver_id, &voucher.creator_id, voucher.royalty);
+
+        let after_storage_usage = env::storage_usage();
+        let storage_cost = env::storage_byte_cost() * Balance::from(after_storage_usage - before_storage_usage);
+        let price = if is_market_sale { 0 } else { voucher.price.0 };
+        let attached_deposit = env::attached_deposit();
+
+        assert!(
+            storage_cost + price <= attached_deposit,
+            "Must attach {} yoctoNear to cover price and storage", storage_cost + price
+        );
+
+        if price > 0 {
+            Promise::new(voucher.creator_id).transfer(price);
+        }
+
+        let refund = attached_deposit - storage_cost - price;
+        if refund > 0 {
+            let refund_id = if is_market_sale { receiver_id } else { predecessor_id };
+            Promise::new(refund_id).transfer(refund);
+        }
+
+        U128(refund)
+    }
+}